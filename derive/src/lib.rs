@@ -41,6 +41,62 @@ fn get_impl_block_zero(ident: &Ident, generics: &Generics) -> impl ToTokens {
     }
 }
 
+fn get_impl_block_for_trait(trait_name: &str, ident: &Ident, generics: &Generics) -> impl ToTokens {
+    let trait_ident = format_ident!("{}", trait_name);
+    let gens2 = generics.params.clone().into_iter().map(|p| match p {
+        GenericParam::Lifetime(lifetime_param) => lifetime_param.lifetime.to_token_stream(),
+        GenericParam::Type(type_param) => type_param.ident.to_token_stream(),
+        GenericParam::Const(const_param) => const_param.ident.to_token_stream(),
+    });
+    let gens = generics.params.clone().into_iter();
+    let clause = &generics.where_clause;
+    quote! { impl<#(#gens ,)*> ::approx_collections::#trait_ident for #ident<#(#gens2 ,)*> #clause }
+}
+
+/// Returns whether a field is marked with `#[approx_internable_non_float]`.
+fn parse_float_attr(field: &Field) -> bool {
+    field.attrs.iter().any(|x| {
+        if let Meta::Path(path) = &x.meta
+            && path.is_ident("approx_internable_non_float")
+        {
+            true
+        } else {
+            false
+        }
+    })
+}
+
+/// Returns a pattern for `path` that binds each field to a variable named
+/// with `prefix`, along with the variables and whether each field is marked
+/// with `#[approx_internable_non_float]`.
+fn get_fields_pattern(
+    path: impl ToTokens,
+    fields: &Fields,
+    prefix: &str,
+) -> (impl ToTokens, Vec<Ident>, Vec<bool>) {
+    let names: Vec<Ident> = match fields {
+        Fields::Named(fields_named) => fields_named
+            .named
+            .iter()
+            .map(|f| format_ident!("{}_{}", prefix, f.ident.as_ref().expect("no field name")))
+            .collect(),
+        Fields::Unnamed(fields_unnamed) => (0..fields_unnamed.unnamed.len())
+            .map(|i| format_ident!("{}_{}", prefix, i))
+            .collect(),
+        Fields::Unit => vec![],
+    };
+    let non_float = fields.iter().map(parse_float_attr).collect();
+    let pattern = match fields {
+        Fields::Named(fields_named) => {
+            let field_names = fields_named.named.iter().map(|f| &f.ident);
+            quote! { #path { #(#field_names: #names,)* } }
+        }
+        Fields::Unnamed(_) => quote! { #path(#(#names,)*) },
+        Fields::Unit => quote! { #path },
+    };
+    (pattern, names, non_float)
+}
+
 fn get_variant_match(variant: &Variant) -> impl ToTokens {
     let ident = &variant.ident;
     match &variant.fields {
@@ -68,70 +124,7 @@ fn get_variant_match(variant: &Variant) -> impl ToTokens {
     }
 }
 
-/// Derives the `ApproxEq` trait on a struct or enum.
-///
-/// This cannot be used on union types.
-///
-/// ## Structs
-///
-/// Two instances of a struct are approximately equal if all of their
-/// corresponding fields are approximately equal.
-///
-/// ```
-/// #[derive(Debug, ApproxEq)]
-/// struct Coordinate {
-///     x: f32,
-///     y: f32,
-/// }
-/// let c1 = Coordinate { x: 5.0, y: 4.0 };
-/// let c2 = Coordinate { x: 4.0, y: 5.0 };
-/// assert!(ApproxEq::approx_eq(&c1, &c1, Precision::DEFAULT));
-/// assert!(!ApproxEq::approx_eq(&c1, &c2, Precision::DEFAULT));
-/// ```
-///
-/// Note that in this example, the `ApproxEq` implementation uses the [taxicab
-/// metric] rather than the [Euclidean metric].
-///
-/// [taxicab metric]: https://en.wikipedia.org/wiki/Taxicab_geometry
-/// [Euclidean metric]: https://en.wikipedia.org/wiki/Euclidean_distance
-///
-/// Tuple structs are also supported.
-///
-/// ```
-/// #[derive(Debug, ApproxEq)]
-/// struct Coordinate(f32, f32);
-///
-/// let c1 = Coordinate(5.0, 4.0);
-/// let c2 = Coordinate(4.0, 5.0);
-/// assert!(ApproxEq::approx_eq(&c1, &c1, Precision::DEFAULT));
-/// assert!(!ApproxEq::approx_eq(&c1, &c2, Precision::DEFAULT));
-/// ```
-///
-/// Two Instances of a unit struct are always approximately equal to each other.
-///
-/// ## Enums
-///
-/// Two instances of an enum are approximately equal if they are the same
-/// variant and the data they contain is approximately equal.
-///
-/// Two instances of the same unit variants of an enum are always approximately
-/// equal to each other.
-///
-/// ```
-/// #[derive(Debug, ApproxEq)]
-/// enum Foo {
-///     Bar1 { data: f32 },
-///     Bar2(f32),
-///     Bar3,
-///     Bar4,
-/// }
-///
-/// assert!(ApproxEq::approx_eq(&Foo::Bar1 { data: 5.0 }, &Foo::Bar1 { data: 5.0 }, Precision::DEFAULT));
-/// assert!(ApproxEq::approx_eq(&Foo::Bar2(5.0), &Foo::Bar2(5.0), Precision::DEFAULT));
-/// assert!(ApproxEq::approx_eq(&Foo::Bar3, &Foo::Bar3, Precision::DEFAULT));
-/// assert!(!ApproxEq::approx_eq(&Foo::Bar1 { data: 5.0 }, &Foo::Bar2(5.0), Precision::DEFAULT));
-/// assert!(!ApproxEq::approx_eq(&Foo::Bar3, &Foo::Bar4, Precision::DEFAULT));
-/// ```
+#[doc = include_str!("../docs/approx_eq.md")]
 #[proc_macro_derive(ApproxEq)]
 pub fn derive_approx_eq(input: TokenStream) -> TokenStream {
     let DeriveInput {
@@ -150,7 +143,7 @@ pub fn derive_approx_eq(input: TokenStream) -> TokenStream {
                     .map(|f| f.ident.as_ref().expect("no field name"));
                 quote! {
                     #impl_block {
-                        fn approx_eq(&self, other: &Self, prec: ::approx_collections::Precision) -> ::std::primitive::bool {
+                        fn approx_eq(&self, other: &Self, prec: ::approx_collections::Precision) -> ::core::primitive::bool {
                             true #(&& ::approx_collections::ApproxEq::approx_eq(&self.#fixed_names, &other.#fixed_names, prec))*
                         }
                     }
@@ -161,7 +154,7 @@ pub fn derive_approx_eq(input: TokenStream) -> TokenStream {
                 let i = (0..fields_unnamed.unnamed.len()).map(syn::Index::from);
                 quote! {
                     #impl_block {
-                        fn approx_eq(&self, other: &Self, prec: ::approx_collections::Precision) -> ::std::primitive::bool {
+                        fn approx_eq(&self, other: &Self, prec: ::approx_collections::Precision) -> ::core::primitive::bool {
                             true #(&& ::approx_collections::ApproxEq::approx_eq(&self.#i, &other.#i, prec))*
                         }
                     }
@@ -170,7 +163,7 @@ pub fn derive_approx_eq(input: TokenStream) -> TokenStream {
             }
             Fields::Unit => quote! {
                 #impl_block {
-                    fn approx_eq(&self, other: &Self, prec: ::approx_collections::Precision) -> ::std::primitive::bool {
+                    fn approx_eq(&self, other: &Self, prec: ::approx_collections::Precision) -> ::core::primitive::bool {
                         true
                     }
                 }
@@ -181,7 +174,7 @@ pub fn derive_approx_eq(input: TokenStream) -> TokenStream {
             let match_inner = data_enum.variants.iter().map(get_variant_match);
             quote! {
                 #impl_block {
-                    fn approx_eq(&self, other: &Self, prec: ::approx_collections::Precision) -> ::std::primitive::bool {
+                    fn approx_eq(&self, other: &Self, prec: ::approx_collections::Precision) -> ::core::primitive::bool {
                         match (self, other) {
                             #(#match_inner,)*
                             _ => false,
@@ -200,28 +193,7 @@ pub fn derive_approx_eq(input: TokenStream) -> TokenStream {
     }
 }
 
-/// Derives `ApproxEqZero` on a struct.
-///
-/// This cannot be used on enums or union types.
-///
-/// ## Structs
-///
-/// A struct is approximately equal to zero if all of its fields are
-/// approximately equal to zero
-///
-/// A struct with no fields is always approximately equal to zero.
-///
-/// ```
-/// #[derive(Debug, ApproxEqZero)]
-/// struct Coordinate {
-///     x: f32,
-///     y: f32,
-/// }
-/// let c1 = Coordinate { x: 0.0, y: 4.0 };
-/// let c2 = Coordinate { x: 0.0, y: 0.0 };
-/// assert!(!ApproxEqZero::approx_eq_zero(&c1, Precision::DEFAULT));
-/// assert!(ApproxEqZero::approx_eq_zero(&c2, Precision::DEFAULT));
-/// ```
+#[doc = include_str!("../docs/approx_eq_zero.md")]
 #[proc_macro_derive(ApproxEqZero)]
 pub fn derive_approx_eq_zero(input: TokenStream) -> TokenStream {
     let DeriveInput {
@@ -240,7 +212,7 @@ pub fn derive_approx_eq_zero(input: TokenStream) -> TokenStream {
                     .map(|f| f.ident.as_ref().expect("no field name"));
                 quote! {
                     #impl_block {
-                        fn approx_eq_zero(&self, prec: ::approx_collections::Precision) -> ::std::primitive::bool {
+                        fn approx_eq_zero(&self, prec: ::approx_collections::Precision) -> ::core::primitive::bool {
                             true #(&& ::approx_collections::ApproxEqZero::approx_eq_zero(&self.#fixed_names, prec))*
                         }
                     }
@@ -251,7 +223,7 @@ pub fn derive_approx_eq_zero(input: TokenStream) -> TokenStream {
                 let i = (0..fields_unnamed.unnamed.len()).map(syn::Index::from);
                 quote! {
                     #impl_block {
-                        fn approx_eq_zero(&self, prec: ::approx_collections::Precision) -> ::std::primitive::bool {
+                        fn approx_eq_zero(&self, prec: ::approx_collections::Precision) -> ::core::primitive::bool {
                             true #(&& ::approx_collections::ApproxEqZero::approx_eq_zero(&self.#i, prec))*
                         }
                     }
@@ -260,7 +232,7 @@ pub fn derive_approx_eq_zero(input: TokenStream) -> TokenStream {
             }
             Fields::Unit => quote! {
                 #impl_block {
-                    fn approx_eq_zero(&self, prec: ::approx_collections::Precision) -> ::std::primitive::bool {
+                    fn approx_eq_zero(&self, prec: ::approx_collections::Precision) -> ::core::primitive::bool {
                         true
                     }
                 }
@@ -282,52 +254,9 @@ pub fn derive_approx_eq_zero(input: TokenStream) -> TokenStream {
     }
 }
 
-/// Derives the `ApproxInternable` trait.
-///
-/// This can be used on structs or enums, but not unions.
-///
-/// When used on a struct, the resulting implementation will call `intern_floats` on every field of the struct.
-///
-/// When used on an enum, the resulting implementation will call `intern_floats` on every field of the current variant.
-///
-/// To mark a field as a non float-based field, use the associated marker attribute `#[approx_internable_non_float]`.
-///
-/// Unit structs and fields need no marker and no floats will be interned.
-///
-/// ```
-/// #[derive(ApproxInternable)]
-/// struct Foo {
-///     bar1: f64,
-///     #[approx_internable_non_float]
-///     bar2: u64,
-/// }
-///
-/// #[derive(ApproxInternable)]
-/// struct Foo2(f64, #[approx_internable_non_float] u64);
-///
-/// #[derive(ApproxInternable)]
-/// enum Foo3 {
-///     Bar1,
-///     Bar2(#[approx_internable_non_float] u64, f64),
-///     Bar3{x: f64, #[approx_internable_non_float] y: u64},
-/// }
-/// ```
-///
-/// Note that you can also use this marker attribute to mark float-based fields you don't want to intern.
-
+#[doc = include_str!("../docs/approx_internable.md")]
 #[proc_macro_derive(ApproxInternable, attributes(approx_internable_non_float))]
 pub fn derive_approx_internable(input: TokenStream) -> TokenStream {
-    fn parse_float_attr(field: &Field) -> bool {
-        field.attrs.iter().any(|x| {
-            if let Meta::Path(path) = &x.meta
-                && path.is_ident("approx_internable_non_float")
-            {
-                true
-            } else {
-                false
-            }
-        })
-    }
     fn get_impl_block_internable(ident: &Ident, generics: &Generics) -> impl ToTokens {
         let gens2 = generics.params.clone().into_iter().map(|p| match p {
             GenericParam::Lifetime(lifetime_param) => lifetime_param.lifetime.to_token_stream(),
@@ -362,7 +291,7 @@ pub fn derive_approx_internable(input: TokenStream) -> TokenStream {
                     let self_names =
                         (0..fields_unnamed.unnamed.len()).map(|x| format_ident!("slf_{}", x));
                     let self_float_names = (0..fields_unnamed.unnamed.len())
-                        .filter(|x| !parse_float_attr(fields_unnamed.unnamed.get(*x).unwrap()))
+                        .filter(|x| !parse_float_attr(&fields_unnamed.unnamed[*x]))
                         .map(|x| format_ident!("slf_{}", x));
                     quote! {Self::#var_name(#(#self_names,)*) => {#(::approx_collections::ApproxInternable::intern_floats(#self_float_names, f);)*},}
                 }
@@ -378,32 +307,29 @@ pub fn derive_approx_internable(input: TokenStream) -> TokenStream {
                         .filter(|f| !parse_float_attr(f))
                         .map(|x| &x.ident);
                     quote! {
-                        fn intern_floats<F: ::std::ops::FnMut(&mut ::std::primitive::f64)>(&mut self, f: &mut F) {
+                        fn intern_floats<F: ::core::ops::FnMut(&mut ::core::primitive::f64)>(&mut self, f: &mut F) {
                             #(::approx_collections::ApproxInternable::intern_floats(&mut self.#float_fields, f);)*
                         }
                     }
                 }
                 Fields::Unnamed(fields_unnamed) => {
                     let float_nums = (0..fields_unnamed.unnamed.len())
-                        .filter(|i| !parse_float_attr(fields_unnamed.unnamed.get(*i).unwrap()))
+                        .filter(|i| !parse_float_attr(&fields_unnamed.unnamed[*i]))
                         .map(Index::from);
                     quote! {
-                        fn intern_floats<F: ::std::ops::FnMut(&mut ::std::primitive::f64)>(&mut self, f: &mut F) {
+                        fn intern_floats<F: ::core::ops::FnMut(&mut ::core::primitive::f64)>(&mut self, f: &mut F) {
                             #(::approx_collections::ApproxInternable::intern_floats(&mut self.#float_nums, f);)*
                         }
                     }
                 }
                 Fields::Unit => quote! {
-                    fn intern_floats<F: ::std::ops::FnMut(&mut ::std::primitive::f64)>(&mut self, f: &mut F) {}
+                    fn intern_floats<F: ::core::ops::FnMut(&mut ::core::primitive::f64)>(&mut self, f: &mut F) {}
                 },
             },
             Data::Enum(data_enum) => {
-                let match_vars = data_enum
-                    .variants
-                    .iter()
-                    .map(|x| get_variant_intern_match(x));
+                let match_vars = data_enum.variants.iter().map(get_variant_intern_match);
                 quote! {
-                    fn intern_floats<F: ::std::ops::FnMut(&mut ::std::primitive::f64)>(&mut self, f: &mut F) {
+                    fn intern_floats<F: ::core::ops::FnMut(&mut ::core::primitive::f64)>(&mut self, f: &mut F) {
                             match self {#(#match_vars)*}
                     }
                 }
@@ -413,8 +339,7 @@ pub fn derive_approx_internable(input: TokenStream) -> TokenStream {
                 Span::mixed_site().into(),
                 "derive(ApproxEqZero) is not implemented for union types.",
             )
-            .into_compile_error()
-            .into(),
+            .into_compile_error(),
         }
     }
     let DeriveInput {
@@ -432,3 +357,248 @@ pub fn derive_approx_internable(input: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+#[doc = include_str!("../docs/approx_hash.md")]
+#[proc_macro_derive(ApproxHash, attributes(approx_internable_non_float))]
+pub fn derive_approx_hash(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident,
+        data,
+        generics,
+        ..
+    } = parse_macro_input!(input);
+    let impl_block = get_impl_block_for_trait("ApproxHash", &ident, &generics);
+    let field_eqs = |names: &[Ident], other_names: &[Ident], non_float: &[bool]| {
+        let eqs = (0..names.len()).map(|i| {
+            let (a, b) = (&names[i], &other_names[i]);
+            if non_float[i] {
+                quote! { ::core::cmp::PartialEq::eq(#a, #b) }
+            } else {
+                quote! { ::approx_collections::ApproxHash::interned_eq(#a, #b) }
+            }
+        });
+        quote! { true #(&& #eqs)* }
+    };
+    let field_hashes = |names: &[Ident], non_float: &[bool]| {
+        let hashes = (0..names.len()).map(|i| {
+            let a = &names[i];
+            if non_float[i] {
+                quote! { ::core::hash::Hash::hash(#a, state); }
+            } else {
+                quote! { ::approx_collections::ApproxHash::interned_hash(#a, state); }
+            }
+        });
+        quote! { #(#hashes)* }
+    };
+    let (eq_body, hash_body) = match &data {
+        Data::Struct(data_struct) => {
+            let (self_pattern, self_names, non_float) =
+                get_fields_pattern(quote! { Self }, &data_struct.fields, "slf");
+            let (other_pattern, other_names, _) =
+                get_fields_pattern(quote! { Self }, &data_struct.fields, "other");
+            let eqs = field_eqs(&self_names, &other_names, &non_float);
+            let hashes = field_hashes(&self_names, &non_float);
+            (
+                quote! {
+                    let #self_pattern = self;
+                    let #other_pattern = other;
+                    #eqs
+                },
+                quote! {
+                    let #self_pattern = self;
+                    #hashes
+                },
+            )
+        }
+        Data::Enum(data_enum) => {
+            let (eq_arms, hash_arms): (Vec<_>, Vec<_>) = data_enum
+                .variants
+                .iter()
+                .map(|variant| {
+                    let var_name = &variant.ident;
+                    let (self_pattern, self_names, non_float) =
+                        get_fields_pattern(quote! { Self::#var_name }, &variant.fields, "slf");
+                    let (other_pattern, other_names, _) =
+                        get_fields_pattern(quote! { Self::#var_name }, &variant.fields, "other");
+                    let eqs = field_eqs(&self_names, &other_names, &non_float);
+                    let hashes = field_hashes(&self_names, &non_float);
+                    (
+                        quote! { (#self_pattern, #other_pattern) => #eqs, },
+                        quote! { #self_pattern => { #hashes } },
+                    )
+                })
+                .unzip();
+            (
+                quote! {
+                    #[allow(unreachable_patterns)]
+                    match (self, other) {
+                        #(#eq_arms)*
+                        _ => false,
+                    }
+                },
+                quote! {
+                    ::core::hash::Hash::hash(&::core::mem::discriminant(self), state);
+                    match self {
+                        #(#hash_arms)*
+                    }
+                },
+            )
+        }
+        Data::Union(_) => {
+            return Error::new(
+                Span::mixed_site().into(),
+                "derive(ApproxHash) is not implemented for union types.",
+            )
+            .into_compile_error()
+            .into();
+        }
+    };
+    quote! {
+        #impl_block {
+            #[allow(unused_variables)]
+            fn interned_eq(&self, other: &Self) -> ::core::primitive::bool {
+                #eq_body
+            }
+
+            #[allow(unused_variables)]
+            fn interned_hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                #hash_body
+            }
+        }
+    }
+    .into()
+}
+
+/// Derives the `ApproxLookup` trait on a struct or enum.
+///
+/// This cannot be used on union types.
+///
+/// The resulting implementation calls `lookup_hash` and `lookup_eq` on every
+/// field, exactly mirroring the implementation from `#[derive(ApproxHash)]`,
+/// so it should only be derived along with `ApproxHash`. Fields marked with
+/// `#[approx_internable_non_float]` are compared and hashed exactly using
+/// `PartialEq` and `Hash` instead.
+///
+/// This allows the type to be looked up in an `ApproxHashMap` using
+/// `get()`, `get_key_value()`, and `contains_key()`.
+#[proc_macro_derive(ApproxLookup, attributes(approx_internable_non_float))]
+pub fn derive_approx_lookup(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident,
+        data,
+        generics,
+        ..
+    } = parse_macro_input!(input);
+    let impl_block = get_impl_block_for_trait("ApproxLookup", &ident, &generics);
+    let field_eqs = |names: &[Ident], other_names: &[Ident], non_float: &[bool]| {
+        let eqs = (0..names.len()).map(|i| {
+            let (a, b) = (&names[i], &other_names[i]);
+            if non_float[i] {
+                quote! { ::core::cmp::PartialEq::eq(#a, #b) }
+            } else {
+                quote! { ::approx_collections::ApproxLookup::lookup_eq(#a, #b, f) }
+            }
+        });
+        quote! { true #(&& #eqs)* }
+    };
+    let field_hashes = |names: &[Ident], non_float: &[bool]| {
+        let hashes = (0..names.len()).map(|i| {
+            let a = &names[i];
+            if non_float[i] {
+                quote! { ::core::hash::Hash::hash(#a, state); }
+            } else {
+                quote! { ::approx_collections::ApproxLookup::lookup_hash(#a, f, state)?; }
+            }
+        });
+        quote! { #(#hashes)* }
+    };
+    let (eq_body, hash_body) = match &data {
+        Data::Struct(data_struct) => {
+            let (self_pattern, self_names, non_float) =
+                get_fields_pattern(quote! { Self }, &data_struct.fields, "slf");
+            let (other_pattern, other_names, _) =
+                get_fields_pattern(quote! { Self }, &data_struct.fields, "other");
+            let eqs = field_eqs(&self_names, &other_names, &non_float);
+            let hashes = field_hashes(&self_names, &non_float);
+            (
+                quote! {
+                    let #self_pattern = self;
+                    let #other_pattern = interned;
+                    #eqs
+                },
+                quote! {
+                    let #self_pattern = self;
+                    #hashes
+                },
+            )
+        }
+        Data::Enum(data_enum) => {
+            let (eq_arms, hash_arms): (Vec<_>, Vec<_>) = data_enum
+                .variants
+                .iter()
+                .map(|variant| {
+                    let var_name = &variant.ident;
+                    let (self_pattern, self_names, non_float) =
+                        get_fields_pattern(quote! { Self::#var_name }, &variant.fields, "slf");
+                    let (other_pattern, other_names, _) =
+                        get_fields_pattern(quote! { Self::#var_name }, &variant.fields, "other");
+                    let eqs = field_eqs(&self_names, &other_names, &non_float);
+                    let hashes = field_hashes(&self_names, &non_float);
+                    (
+                        quote! { (#self_pattern, #other_pattern) => #eqs, },
+                        quote! { #self_pattern => { #hashes } },
+                    )
+                })
+                .unzip();
+            (
+                quote! {
+                    #[allow(unreachable_patterns)]
+                    match (self, interned) {
+                        #(#eq_arms)*
+                        _ => false,
+                    }
+                },
+                quote! {
+                    ::core::hash::Hash::hash(&::core::mem::discriminant(self), state);
+                    match self {
+                        #(#hash_arms)*
+                    }
+                },
+            )
+        }
+        Data::Union(_) => {
+            return Error::new(
+                Span::mixed_site().into(),
+                "derive(ApproxLookup) is not implemented for union types.",
+            )
+            .into_compile_error()
+            .into();
+        }
+    };
+    quote! {
+        #impl_block {
+            #[allow(unused_variables)]
+            fn lookup_hash<F, H>(
+                &self,
+                f: &mut F,
+                state: &mut H,
+            ) -> ::core::option::Option<()>
+            where
+                F: ::core::ops::FnMut(::core::primitive::f64) -> ::core::option::Option<::core::primitive::f64>,
+                H: ::core::hash::Hasher,
+            {
+                #hash_body
+                ::core::option::Option::Some(())
+            }
+
+            #[allow(unused_variables)]
+            fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> ::core::primitive::bool
+            where
+                F: ::core::ops::FnMut(::core::primitive::f64) -> ::core::option::Option<::core::primitive::f64>,
+            {
+                #eq_body
+            }
+        }
+    }
+    .into()
+}
@@ -0,0 +1,360 @@
+//! Bidirectional map between [`ApproxHash`]able values.
+
+use core::fmt;
+
+use crate::hash_map::{self, ApproxHashMap};
+use crate::{ApproxHash, ApproxOrd, FloatPool, Precision};
+
+/// One-to-one map between left and right values, which can be looked up from
+/// either side, where approximately equal values are considered equal.
+///
+/// Each side has its own [`FloatPool`] and precision. To use values without
+/// floats on one side, such as integer IDs, wrap them in [`Exact`](crate::Exact)
+/// so that they are compared exactly.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxBiMap, Exact, Precision};
+///
+/// let mut vertex_ids = ApproxBiMap::new(Precision::DEFAULT);
+/// vertex_ids.insert([0.3, 1.0], Exact(0));
+/// vertex_ids.insert([2.0, 0.5], Exact(1));
+///
+/// assert_eq!(vertex_ids.get_by_left([0.1 + 0.2, 1.0]), Some(&Exact(0)));
+/// assert_eq!(vertex_ids.get_by_right(Exact(1)), Some(&[2.0, 0.5]));
+///
+/// assert_eq!(vertex_ids.remove_by_right(Exact(0)), Some(([0.3, 1.0], Exact(0))));
+/// assert!(!vertex_ids.contains_left([0.3, 1.0]));
+/// ```
+#[derive(Clone)]
+pub struct ApproxBiMap<L, R> {
+    left: ApproxHashMap<L, R>,
+    right: ApproxHashMap<R, L>,
+}
+
+impl<L, R> fmt::Debug for ApproxBiMap<L, R>
+where
+    L: fmt::Debug + ApproxOrd,
+    R: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.left, f)
+    }
+}
+
+impl<L, R> Default for ApproxBiMap<L, R> {
+    /// Constructs an empty map using [`Precision::default()`] for both sides.
+    fn default() -> Self {
+        Self::new(Precision::default())
+    }
+}
+
+impl<L, R> ApproxBiMap<L, R> {
+    /// Constructs an empty map that uses `prec` for both sides.
+    pub fn new(prec: Precision) -> Self {
+        Self::with_precisions(prec, prec)
+    }
+
+    /// Constructs an empty map that uses a different precision for each side.
+    pub fn with_precisions(left_prec: Precision, right_prec: Precision) -> Self {
+        Self {
+            left: ApproxHashMap::new(left_prec),
+            right: ApproxHashMap::new(right_prec),
+        }
+    }
+
+    /// Returns an iterator of all the pairs in the map, in an undefined order.
+    pub fn iter(&self) -> hash_map::Iter<'_, L, R> {
+        self.left.iter()
+    }
+
+    /// Returns an iterator of all the left values in the map, in an undefined
+    /// order.
+    pub fn left_values(&self) -> hash_map::Keys<'_, L, R> {
+        self.left.keys()
+    }
+
+    /// Returns an iterator of all the right values in the map, in an undefined
+    /// order.
+    pub fn right_values(&self) -> hash_map::Keys<'_, R, L> {
+        self.right.keys()
+    }
+
+    /// Returns the number of pairs in the map.
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+
+    /// Returns whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty()
+    }
+
+    /// Clears the map, removing all pairs. Keeps the interned floats.
+    pub fn clear(&mut self) {
+        self.left.clear();
+        self.right.clear();
+    }
+
+    /// Returns a reference to the [`FloatPool`] for left values.
+    pub fn left_float_pool(&self) -> &FloatPool {
+        self.left.float_pool()
+    }
+
+    /// Returns a reference to the [`FloatPool`] for right values.
+    pub fn right_float_pool(&self) -> &FloatPool {
+        self.right.float_pool()
+    }
+
+    /// Returns the precision used to compare left values.
+    pub fn left_prec(&self) -> Precision {
+        self.left.prec()
+    }
+
+    /// Returns the precision used to compare right values.
+    pub fn right_prec(&self) -> Precision {
+        self.right.prec()
+    }
+}
+
+impl<L, R> ApproxBiMap<L, R>
+where
+    L: ApproxHash,
+    R: ApproxHash,
+{
+    /// Returns the right value paired with a left value (or something
+    /// approximately equal).
+    pub fn get_by_left(&self, left: L) -> Option<&R> {
+        self.left.get_owned(left)
+    }
+
+    /// Returns the left value paired with a right value (or something
+    /// approximately equal).
+    pub fn get_by_right(&self, right: R) -> Option<&L> {
+        self.right.get_owned(right)
+    }
+
+    /// Returns whether the map contains a left value (or something
+    /// approximately equal).
+    pub fn contains_left(&self, left: L) -> bool {
+        self.left.contains_key_owned(left)
+    }
+
+    /// Returns whether the map contains a right value (or something
+    /// approximately equal).
+    pub fn contains_right(&self, right: R) -> bool {
+        self.right.contains_key_owned(right)
+    }
+
+    /// Removes the pair containing a left value (or something approximately
+    /// equal) and returns it, or `None` if it was not present.
+    pub fn remove_by_left(&mut self, left: L) -> Option<(L, R)> {
+        let (_, right) = self.left.remove_entry(left)?;
+        let (right, left) = (self.right.remove_entry(right)).expect("missing value in ApproxBiMap");
+        Some((left, right))
+    }
+
+    /// Removes the pair containing a right value (or something approximately
+    /// equal) and returns it, or `None` if it was not present.
+    pub fn remove_by_right(&mut self, right: R) -> Option<(L, R)> {
+        let (_, left) = self.right.remove_entry(right)?;
+        let (left, right) = (self.left.remove_entry(left)).expect("missing value in ApproxBiMap");
+        Some((left, right))
+    }
+}
+
+impl<L, R> ApproxBiMap<L, R>
+where
+    L: ApproxHash + Clone,
+    R: ApproxHash + Clone,
+{
+    /// Constructs a map from an iterator of pairs, using `prec` for both
+    /// sides.
+    pub fn from_iter<T: IntoIterator<Item = (L, R)>>(prec: Precision, iter: T) -> Self {
+        let mut map = Self::new(prec);
+        map.extend(iter);
+        map
+    }
+
+    /// Inserts a pair into the map. Both values are interned first.
+    ///
+    /// Any existing pairs containing the left value or the right value (or
+    /// something approximately equal) are removed and returned.
+    pub fn insert(&mut self, left: L, right: R) -> Overwritten<L, R> {
+        let left = self.left.intern(left);
+        let right = self.right.intern(right);
+        let old_left = self.remove_by_left(left.clone());
+        let old_right = self.remove_by_right(right.clone());
+        let same_pair = old_left
+            .as_ref()
+            .is_some_and(|(_, r)| r.interned_eq(&right));
+        self.insert_unchecked(left, right);
+        match (old_left, old_right) {
+            (None, None) => Overwritten::Neither,
+            (Some((l, r)), None) if same_pair => Overwritten::Pair(l, r),
+            (Some((l, r)), None) => Overwritten::Left(l, r),
+            (None, Some((l, r))) => Overwritten::Right(l, r),
+            (Some(old_left), Some(old_right)) => Overwritten::Both(old_left, old_right),
+        }
+    }
+
+    /// Inserts a pair into the map if neither value (or something
+    /// approximately equal) is already present. Otherwise returns the pair
+    /// unmodified.
+    pub fn insert_no_overwrite(&mut self, left: L, right: R) -> Result<(), (L, R)> {
+        if self.contains_left(left.clone()) || self.contains_right(right.clone()) {
+            return Err((left, right));
+        }
+        let left = self.left.intern(left);
+        let right = self.right.intern(right);
+        self.insert_unchecked(left, right);
+        Ok(())
+    }
+
+    /// Inserts a pair of interned values, neither of which is present.
+    fn insert_unchecked(&mut self, left: L, right: R) {
+        let hash = self.left.hash_interned(&left);
+        self.left
+            .entry_interned(hash, left.clone())
+            .or_insert(right.clone());
+        let hash = self.right.hash_interned(&right);
+        self.right.entry_interned(hash, right).or_insert(left);
+    }
+}
+
+/// Pairs that were removed from an [`ApproxBiMap`] by
+/// [`insert()`](ApproxBiMap::insert).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overwritten<L, R> {
+    /// Neither value was present.
+    Neither,
+    /// The same pair (or something approximately equal) was present.
+    Pair(L, R),
+    /// The left value was present, paired with a different right value.
+    Left(L, R),
+    /// The right value was present, paired with a different left value.
+    Right(L, R),
+    /// The left value and the right value were both present in different
+    /// pairs. The pair containing the left value is first.
+    Both((L, R), (L, R)),
+}
+
+impl<L, R> Overwritten<L, R> {
+    /// Returns whether any pairs were removed.
+    pub fn did_overwrite(&self) -> bool {
+        !matches!(self, Overwritten::Neither)
+    }
+}
+
+impl<L, R> Extend<(L, R)> for ApproxBiMap<L, R>
+where
+    L: ApproxHash + Clone,
+    R: ApproxHash + Clone,
+{
+    fn extend<T: IntoIterator<Item = (L, R)>>(&mut self, iter: T) {
+        for (l, r) in iter {
+            self.insert(l, r);
+        }
+    }
+}
+
+impl<L, R> FromIterator<(L, R)> for ApproxBiMap<L, R>
+where
+    L: ApproxHash + Clone,
+    R: ApproxHash + Clone,
+{
+    /// Constructs a map using [`Precision::default()`] for both sides from an
+    /// iterator of pairs.
+    fn from_iter<T: IntoIterator<Item = (L, R)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<L, R> IntoIterator for ApproxBiMap<L, R> {
+    type Item = (L, R);
+
+    type IntoIter = hash_map::IntoIter<L, R>;
+
+    /// Converts the map into an iterator of all its pairs, in an undefined
+    /// order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.left.into_iter()
+    }
+}
+impl<'a, L, R> IntoIterator for &'a ApproxBiMap<L, R> {
+    type Item = (&'a L, &'a R);
+
+    type IntoIter = hash_map::Iter<'a, L, R>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::Exact;
+
+    #[test]
+    fn test_bi_map() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut map = ApproxBiMap::new(prec);
+        assert_eq!(map.insert(1.0, [2.0, 3.0]), Overwritten::Neither);
+        assert_eq!(map.insert(4.0, [5.0, 6.0]), Overwritten::Neither);
+        assert_eq!(map.len(), 2);
+
+        assert_eq!(map.get_by_left(1.01), Some(&[2.0, 3.0]));
+        assert_eq!(map.get_by_right([4.99, 6.01]), Some(&4.0));
+        assert_eq!(map.get_by_left(2.0), None);
+        assert!(map.contains_right([2.0, 2.99]));
+        assert!(!map.contains_right([2.0, 4.0]));
+
+        assert_eq!(map.remove_by_left(0.99), Some((1.0, [2.0, 3.0])));
+        assert_eq!(map.remove_by_right([2.0, 3.0]), None);
+        assert_eq!(map.remove_by_right([5.0, 6.0]), Some((4.0, [5.0, 6.0])));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_bi_map_exact() {
+        let mut map = ApproxBiMap::with_precisions(Precision::DEFAULT, Precision::absolute(3));
+        map.extend([(Exact(1), 1.0), (Exact(2), 2.0)]);
+        assert_eq!(map.get_by_right(1.01), Some(&Exact(1)));
+        assert_eq!(map.get_by_left(Exact(2)), Some(&2.0));
+        assert_eq!(map.get_by_left(Exact(3)), None);
+        assert_eq!(format!("{:?}", map.get_by_right(2.0)), "Some(Exact(2))");
+    }
+
+    #[test]
+    fn test_bi_map_overwrite() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut map = ApproxBiMap::from_iter(prec, [(1.0, 10.0), (2.0, 20.0), (3.0, 30.0)]);
+
+        // Overwrites both (1.0, 10.0) and (2.0, 20.0).
+        assert_eq!(
+            map.insert(1.01, 20.01),
+            Overwritten::Both((1.0, 10.0), (2.0, 20.0)),
+        );
+        assert_eq!(map.get_by_left(1.0), Some(&20.0));
+        assert_eq!(map.get_by_right(20.0), Some(&1.0));
+        assert!(!map.contains_left(2.0));
+        assert!(!map.contains_right(10.0));
+
+        // Same pair
+        assert_eq!(map.insert(3.0, 30.01), Overwritten::Pair(3.0, 30.0));
+        assert_eq!(map.insert(3.0, 40.0), Overwritten::Left(3.0, 30.0));
+        assert_eq!(map.insert(4.0, 40.0), Overwritten::Right(3.0, 40.0));
+        assert!(!map.insert(3.0, 30.0).did_overwrite());
+        assert_eq!(map.len(), 3);
+
+        assert_eq!(map.insert_no_overwrite(3.0, 50.0), Err((3.0, 50.0)));
+        assert_eq!(map.insert_no_overwrite(5.0, 50.0), Ok(()));
+        assert_eq!(map.len(), 4);
+    }
+}
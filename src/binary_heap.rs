@@ -0,0 +1,371 @@
+//! Priority queue that works for [`ApproxOrd`]ered keys.
+
+use alloc::collections::{BinaryHeap, binary_heap};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FusedIterator;
+
+use crate::hash_map::SORT_PREC;
+use crate::{ApproxInternable, ApproxOrd, FloatPool, Precision};
+
+type IntoIterInner<K, V> =
+    core::iter::Map<binary_heap::IntoIter<HeapEntry<K, V>>, fn(HeapEntry<K, V>) -> (K, V)>;
+type IterInner<'a, K, V> = core::iter::Map<
+    binary_heap::Iter<'a, HeapEntry<K, V>>,
+    fn(&'a HeapEntry<K, V>) -> (&'a K, &'a V),
+>;
+
+/// Entry stored in an [`ApproxBinaryHeap`], ordered by key using [`ApproxOrd`]
+/// and then by insertion order.
+///
+/// Keys are compared at [`SORT_PREC`] rather than the heap's precision, because
+/// distinct interned keys may still be approximately equal, and comparing them
+/// at the heap's precision would not be a total order.
+#[derive(Debug, Clone)]
+struct HeapEntry<K, V> {
+    key: K,
+    /// Sequence number, used to pop entries with equal keys in the order they
+    /// were pushed.
+    seq: u64,
+    value: V,
+}
+
+impl<K: ApproxOrd, V> PartialEq for HeapEntry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl<K: ApproxOrd, V> Eq for HeapEntry<K, V> {}
+impl<K: ApproxOrd, V> PartialOrd for HeapEntry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<K: ApproxOrd, V> Ord for HeapEntry<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.key.approx_cmp(&other.key, SORT_PREC)).then(other.seq.cmp(&self.seq))
+    }
+}
+
+/// Approximate priority queue for objects with floating-point keys, ordered
+/// using [`ApproxOrd`].
+///
+/// This is a max-heap; use [`Reverse`](core::cmp::Reverse) keys for a min-heap.
+/// Keys are interned using a [`FloatPool`] before they are pushed, so most
+/// approximately equal keys become exactly equal. Entries with equal keys are
+/// popped in the order they were pushed.
+///
+/// # Examples
+///
+/// ```
+/// use std::cmp::Reverse;
+///
+/// use approx_collections::{ApproxBinaryHeap, Precision};
+///
+/// let mut events = ApproxBinaryHeap::new(Precision::DEFAULT);
+/// events.push(Reverse(0.3), "c");
+/// events.push(Reverse(0.1), "a");
+/// events.push(Reverse(0.1 + 0.2), "b");
+///
+/// assert_eq!(events.pop(), Some((Reverse(0.1), "a")));
+/// assert_eq!(events.pop_ties(), [(Reverse(0.3), "c"), (Reverse(0.3), "b")]);
+/// assert!(events.is_empty());
+/// ```
+#[derive(Clone)]
+pub struct ApproxBinaryHeap<K, V> {
+    pool: FloatPool,
+    heap: BinaryHeap<HeapEntry<K, V>>,
+    next_seq: u64,
+}
+
+impl<K, V> fmt::Debug for ApproxBinaryHeap<K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for ApproxBinaryHeap<K, V> {
+    /// Constructs an empty heap using [`Precision::default()`].
+    fn default() -> Self {
+        Self::new(Precision::default())
+    }
+}
+
+impl<K, V> ApproxBinaryHeap<K, V> {
+    /// Constructs an empty heap.
+    pub fn new(prec: Precision) -> Self {
+        Self {
+            pool: FloatPool::new(prec),
+            heap: BinaryHeap::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Returns an iterator of all the entries in the heap, in an undefined
+    /// order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.heap.iter().map(|e| (&e.key, &e.value)),
+        }
+    }
+
+    /// Returns the number of entries in the heap.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns whether the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Clears the heap, removing all entries. Keeps the interned floats.
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+
+    /// Returns a reference to the heap's [`FloatPool`].
+    pub fn float_pool(&self) -> &FloatPool {
+        &self.pool
+    }
+
+    /// Returns the precision used to compare keys.
+    pub fn prec(&self) -> Precision {
+        self.pool.prec()
+    }
+
+    /// Returns the entry with the greatest key, or `None` if the heap is
+    /// empty.
+    pub fn peek(&self) -> Option<(&K, &V)> {
+        self.heap.peek().map(|e| (&e.key, &e.value))
+    }
+}
+
+impl<K: ApproxOrd + ApproxInternable, V> ApproxBinaryHeap<K, V> {
+    /// Constructs a heap from an iterator of keys and values.
+    pub fn from_iter<T: IntoIterator<Item = (K, V)>>(prec: Precision, iter: T) -> Self {
+        let mut heap = Self::new(prec);
+        heap.extend(iter);
+        heap
+    }
+
+    /// Pushes an entry onto the heap. The key is interned first.
+    pub fn push(&mut self, key: K, value: V) {
+        let key = self.pool.intern(key);
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.push(HeapEntry { key, seq, value });
+    }
+
+    /// Removes the entry with the greatest key and returns it, or `None` if
+    /// the heap is empty.
+    ///
+    /// If multiple entries have the same key, the one that was pushed first is
+    /// returned.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        self.heap.pop().map(|e| (e.key, e.value))
+    }
+
+    /// Removes all the entries whose keys are approximately equal to the
+    /// greatest key and returns them in the order that
+    /// [`ApproxBinaryHeap::pop()`] would return them.
+    ///
+    /// Returns an empty vector if the heap is empty.
+    pub fn pop_ties(&mut self) -> Vec<(K, V)> {
+        let prec = self.prec();
+        let mut ret: Vec<(K, V)> = Vec::new();
+        while let Some(top) = self.heap.peek() {
+            if let Some((first_key, _)) = ret.first()
+                && top.key.approx_cmp(first_key, prec) != Ordering::Equal
+            {
+                break;
+            }
+            ret.extend(self.pop());
+        }
+        ret
+    }
+
+    /// Consumes the heap and returns a vector of its entries, in the reverse of
+    /// the order they would be popped.
+    pub fn into_sorted_vec(self) -> Vec<(K, V)> {
+        (self.heap.into_sorted_vec().into_iter())
+            .map(|e| (e.key, e.value))
+            .collect()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for ApproxBinaryHeap<K, V>
+where
+    K: ApproxOrd + ApproxInternable,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.push(k, v);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for ApproxBinaryHeap<K, V>
+where
+    K: ApproxOrd + ApproxInternable,
+{
+    /// Constructs a heap using [`Precision::default()`] from an iterator of
+    /// keys and values.
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut heap = Self::default();
+        heap.extend(iter);
+        heap
+    }
+}
+
+impl<K, V> IntoIterator for ApproxBinaryHeap<K, V> {
+    type Item = (K, V);
+
+    type IntoIter = IntoIter<K, V>;
+
+    /// Converts the heap into an iterator of all its entries, in an undefined
+    /// order.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.heap.into_iter().map(|e| (e.key, e.value)),
+        }
+    }
+}
+impl<'a, K, V> IntoIterator for &'a ApproxBinaryHeap<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An owning iterator over the entries of an `ApproxBinaryHeap`.
+#[derive(Debug)]
+pub struct IntoIter<K, V> {
+    inner: IntoIterInner<K, V>,
+}
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl<K, V> FusedIterator for IntoIter<K, V> {}
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An iterator over the entries of an `ApproxBinaryHeap`.
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    inner: IterInner<'a, K, V>,
+}
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cmp::Reverse;
+
+    use super::*;
+
+    #[test]
+    fn test_binary_heap() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut heap = ApproxBinaryHeap::new(prec);
+        heap.push(1.0, 'a');
+        heap.push(3.0, 'b');
+        heap.push(2.99, 'c');
+        heap.push(2.0, 'd');
+        heap.push(3.01, 'e');
+        assert_eq!(heap.len(), 5);
+        assert_eq!(heap.iter().len(), 5);
+
+        assert_eq!(heap.peek(), Some((&3.0, &'b')));
+        assert_eq!(heap.pop_ties(), [(3.0, 'b'), (3.0, 'c'), (3.0, 'e')]);
+        assert_eq!(heap.pop_ties(), [(2.0, 'd')]);
+        heap.push(1.01, 'f');
+        assert_eq!(heap.pop(), Some((1.0, 'a')));
+        assert_eq!(heap.pop(), Some((1.0, 'f')));
+        assert_eq!(heap.pop(), None);
+        assert!(heap.pop_ties().is_empty());
+    }
+
+    #[test]
+    fn test_binary_heap_reverse() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let heap = ApproxBinaryHeap::from_iter(
+            prec,
+            [
+                (Reverse(2.0), 'a'),
+                (Reverse(1.0), 'b'),
+                (Reverse(2.01), 'c'),
+            ],
+        );
+        let values: Vec<char> = heap.into_sorted_vec().into_iter().map(|(_, v)| v).collect();
+        assert_eq!(values, ['c', 'a', 'b']);
+    }
+
+    #[test]
+    fn test_binary_heap_neighboring_keys() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        // 0.0 is approximately equal to both 0.15 and -0.15, which are not
+        // approximately equal to each other.
+        let entries = [(0.0, 'z'), (0.15, 'p'), (-0.15, 'n'), (0.15, 'q')];
+        let mut heap = ApproxBinaryHeap::from_iter(prec, entries);
+        assert_eq!(heap.pop(), Some((0.15, 'p')));
+        assert_eq!(heap.pop(), Some((0.15, 'q')));
+        assert_eq!(heap.pop(), Some((0.0, 'z')));
+        assert_eq!(heap.pop(), Some((-0.15, 'n')));
+
+        let mut heap = ApproxBinaryHeap::from_iter(prec, entries);
+        assert_eq!(heap.pop_ties(), [(0.15, 'p'), (0.15, 'q'), (0.0, 'z')]);
+        assert_eq!(heap.pop_ties(), [(-0.15, 'n')]);
+    }
+}
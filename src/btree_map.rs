@@ -0,0 +1,853 @@
+//! Ordered map that works for [`ApproxOrd`]ered values.
+
+use alloc::collections::{BTreeMap, btree_map};
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Bound, RangeBounds};
+
+use crate::{ApproxInternable, ApproxOrd, FloatPool, Precision};
+
+/// Key stored in an [`ApproxBTreeMap`], ordered using [`ApproxOrd`].
+///
+/// Keys in the map are always interned, so distinct keys are never
+/// approximately equal and the order is consistent.
+#[derive(Debug, Clone)]
+pub(crate) struct OrdKey<K> {
+    pub(crate) key: K,
+    pub(crate) prec: Precision,
+}
+
+impl<K: ApproxOrd> PartialEq for OrdKey<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl<K: ApproxOrd> Eq for OrdKey<K> {}
+impl<K: ApproxOrd> PartialOrd for OrdKey<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<K: ApproxOrd> Ord for OrdKey<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.approx_cmp(&other.key, self.prec)
+    }
+}
+
+/// Approximate ordered map for objects with floating-point values, ordered
+/// using [`ApproxOrd`].
+///
+/// Keys are interned using a [`FloatPool`] before they are inserted. Distinct
+/// interned keys are never approximately equal, so [`ApproxOrd::approx_cmp()`]
+/// is consistent on the keys in the map even though it is not transitive in
+/// general.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxBTreeMap, Precision};
+///
+/// let mut events = ApproxBTreeMap::new(Precision::DEFAULT);
+/// events.insert(2.5, "c");
+/// events.insert(0.5, "a");
+/// events.insert(1.0, "b");
+///
+/// let between: Vec<_> = events.range(1.0..=2.0).map(|(_, &v)| v).collect();
+/// assert_eq!(between, ["b"]);
+/// assert_eq!(events.first_key_value(), Some((&0.5, &"a")));
+/// ```
+#[derive(Clone)]
+pub struct ApproxBTreeMap<K, V> {
+    pool: FloatPool,
+    map: BTreeMap<OrdKey<K>, V>,
+}
+
+impl<K, V> fmt::Debug for ApproxBTreeMap<K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V> Default for ApproxBTreeMap<K, V> {
+    /// Constructs an empty map using [`Precision::default()`].
+    fn default() -> Self {
+        Self::new(Precision::default())
+    }
+}
+
+impl<K, V> ApproxBTreeMap<K, V> {
+    /// Constructs an empty map.
+    pub fn new(prec: Precision) -> Self {
+        Self {
+            pool: FloatPool::new(prec),
+            map: BTreeMap::new(),
+        }
+    }
+
+    /// Returns an iterator of all the entries in the map, sorted by key.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.map.iter().map(|(k, v)| (&k.key, v)),
+        }
+    }
+    /// Returns an iterator of mutable references to all the entries in the
+    /// map, sorted by key.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.map.iter_mut().map(|(k, v)| (&k.key, v)),
+        }
+    }
+
+    /// Returns an iterator of all the keys in the map, in sorted order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.map.keys().map(|k| &k.key),
+        }
+    }
+    /// Converts the map into an iterator of all its keys, in sorted order.
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys {
+            inner: self.map.into_keys().map(|k| k.key),
+        }
+    }
+
+    /// Returns an iterator of all the values in the map, sorted by key.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            #[allow(clippy::map_identity)] // needed for full generality of the macro
+            inner: self.map.values().map(|v| v),
+        }
+    }
+    /// Returns an iterator of mutable references to all the values in the map,
+    /// sorted by key.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            #[allow(clippy::map_identity)] // needed for full generality of the macro
+            inner: self.map.values_mut().map(|v| v),
+        }
+    }
+    /// Converts the map into an iterator of all its values, sorted by key.
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues {
+            #[allow(clippy::map_identity)] // needed for full generality of the macro
+            inner: self.map.into_values().map(|v| v),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Clears the map, removing all key-value pairs. Keeps the interned floats.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns a reference to the map's [`FloatPool`].
+    pub fn float_pool(&self) -> &FloatPool {
+        &self.pool
+    }
+
+    /// Returns the precision used to compare floats.
+    pub fn prec(&self) -> Precision {
+        self.pool.prec()
+    }
+}
+
+impl<K, V> ApproxBTreeMap<K, V>
+where
+    K: ApproxInternable + ApproxOrd,
+{
+    /// Constructs an `ApproxBTreeMap<K, V>` from an iterator of key-value
+    /// pairs.
+    ///
+    /// If the iterator produces any pairs with approximately equal keys, all
+    /// but one of the corresponding values will be dropped.
+    pub fn from_iter<T: IntoIterator<Item = (K, V)>>(prec: Precision, iter: T) -> Self {
+        let mut map = Self::new(prec);
+        map.extend(iter);
+        map
+    }
+
+    /// Returns an entry in the map for in-place manipulation.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let key = self.intern_key(key);
+        match self.map.entry(key) {
+            btree_map::Entry::Occupied(inner) => Entry::Occupied(OccupiedEntry { inner }),
+            btree_map::Entry::Vacant(inner) => Entry::Vacant(VacantEntry { inner }),
+        }
+    }
+    /// Returns the value in the map associated to the given key (or something
+    /// approximately equal).
+    pub fn get(&self, key: K) -> Option<&V> {
+        Some(self.get_key_value(key)?.1)
+    }
+    /// Returns the existing key-value pair that corresponds to the given key,
+    /// or `None` if it is not present.
+    pub fn get_key_value(&self, key: K) -> Option<(&K, &V)> {
+        // Early exit optimization; don't bother interning
+        if self.is_empty() {
+            return None;
+        }
+
+        let key = self.try_intern_key(key)?;
+        let (k, v) = self.map.get_key_value(&key)?;
+        Some((&k.key, v))
+    }
+    /// Returns whether the map contains a key.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+    /// Returns a mutable reference to the value corresponding to a key.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        let key = self.try_intern_key(key)?;
+        self.map.get_mut(&key)
+    }
+    /// Inserts an entry into the map and returns the old value, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut e) => Some(e.insert(value)),
+            Entry::Vacant(e) => {
+                e.insert(value);
+                None
+            }
+        }
+    }
+    /// Removes an entry from the map and returns the value, or `None` if the
+    /// key was not present.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        Some(self.remove_entry(key)?.1)
+    }
+    /// Removes an entry from the map and returns the key-value pair, or `None`
+    /// if the key was not present.
+    pub fn remove_entry(&mut self, key: K) -> Option<(K, V)> {
+        let key = self.try_intern_key(key)?;
+        let (k, v) = self.map.remove_entry(&key)?;
+        Some((k.key, v))
+    }
+
+    /// Returns the first key-value pair in the map, which has the minimum key.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let (k, v) = self.map.first_key_value()?;
+        Some((&k.key, v))
+    }
+    /// Returns the last key-value pair in the map, which has the maximum key.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let (k, v) = self.map.last_key_value()?;
+        Some((&k.key, v))
+    }
+
+    /// Removes and returns the first entry in the map, which has the minimum
+    /// key.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let (k, v) = self.map.pop_first()?;
+        Some((k.key, v))
+    }
+    /// Removes and returns the last entry in the map, which has the maximum
+    /// key.
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let (k, v) = self.map.pop_last()?;
+        Some((k.key, v))
+    }
+
+    /// Moves all entries from `other` into `self`, leaving `other` empty.
+    ///
+    /// Keys from `other` are interned using the [`FloatPool`] of `self`. If a
+    /// key from `other` is approximately equal to a key in `self`, the value
+    /// from `other` replaces the value in `self`.
+    pub fn append(&mut self, other: &mut Self) {
+        let other_map = core::mem::take(&mut other.map);
+        self.extend(other_map.into_iter().map(|(k, v)| (k.key, v)));
+    }
+    /// Splits the map in two at `key`. Returns a new map containing all the
+    /// entries with keys greater than or approximately equal to `key`, which
+    /// are removed from `self`.
+    ///
+    /// The new map starts with a copy of the [`FloatPool`] of `self`.
+    pub fn split_off(&mut self, key: &K) -> Self
+    where
+        K: Clone,
+    {
+        let key = OrdKey {
+            key: self.pool.intern_existing(key.clone()),
+            prec: self.prec(),
+        };
+        Self {
+            pool: self.pool.clone(),
+            map: self.map.split_off(&key),
+        }
+    }
+
+    /// Retains only the entries for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.map.retain(|k, v| f(&k.key, v));
+    }
+
+    /// Returns an iterator over the entries with keys in `range`, sorted by
+    /// key.
+    ///
+    /// Bounds are compared approximately, so an entry whose key is
+    /// approximately equal to an included bound is yielded and an entry whose
+    /// key is approximately equal to an excluded bound is not.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if they are
+    /// approximately equal and both excluded.
+    pub fn range<R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Clone,
+        R: RangeBounds<K>,
+    {
+        let start = self.bound_key(range.start_bound());
+        let end = self.bound_key(range.end_bound());
+        Range {
+            inner: self.map.range((start, end)).map(|(k, v)| (&k.key, v)),
+        }
+    }
+    /// Returns an iterator of mutable references to the entries with keys in
+    /// `range`, sorted by key.
+    ///
+    /// See [`ApproxBTreeMap::range()`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if they are
+    /// approximately equal and both excluded.
+    pub fn range_mut<R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        K: Clone,
+        R: RangeBounds<K>,
+    {
+        let start = self.bound_key(range.start_bound());
+        let end = self.bound_key(range.end_bound());
+        RangeMut {
+            inner: self.map.range_mut((start, end)).map(|(k, v)| (&k.key, v)),
+        }
+    }
+
+    /// Returns a [`Cursor`] pointing at the gap before the smallest key that is
+    /// above `bound`.
+    ///
+    /// - `Bound::Included(x)` points before the smallest key `>= x`
+    /// - `Bound::Excluded(x)` points before the smallest key `> x`
+    /// - `Bound::Unbounded` points before the first key in the map
+    pub fn lower_bound(&self, bound: Bound<&K>) -> Cursor<'_, K, V>
+    where
+        K: Clone,
+    {
+        let prev = match self.bound_key(bound) {
+            Bound::Included(k) => self.map.range(..k).next_back(),
+            Bound::Excluded(k) => self.map.range(..=k).next_back(),
+            Bound::Unbounded => None,
+        };
+        Cursor {
+            map: &self.map,
+            prev: prev.map(|(k, _v)| k),
+        }
+    }
+    /// Returns a [`Cursor`] pointing at the gap after the greatest key that is
+    /// below `bound`.
+    ///
+    /// - `Bound::Included(x)` points after the greatest key `<= x`
+    /// - `Bound::Excluded(x)` points after the greatest key `< x`
+    /// - `Bound::Unbounded` points after the last key in the map
+    pub fn upper_bound(&self, bound: Bound<&K>) -> Cursor<'_, K, V>
+    where
+        K: Clone,
+    {
+        let prev = match self.bound_key(bound) {
+            Bound::Included(k) => self.map.range(..=k).next_back(),
+            Bound::Excluded(k) => self.map.range(..k).next_back(),
+            Bound::Unbounded => self.map.last_key_value(),
+        };
+        Cursor {
+            map: &self.map,
+            prev: prev.map(|(k, _v)| k),
+        }
+    }
+
+    fn intern_key(&mut self, key: K) -> OrdKey<K> {
+        let prec = self.prec();
+        let key = self.pool.intern(key);
+        OrdKey { key, prec }
+    }
+    fn try_intern_key(&self, key: K) -> Option<OrdKey<K>> {
+        let prec = self.prec();
+        let key = self.pool.try_intern(key)?;
+        Some(OrdKey { key, prec })
+    }
+    /// Converts a bound on keys into a bound on [`OrdKey`]s, interning the
+    /// floats that are already in the pool.
+    fn bound_key(&self, bound: Bound<&K>) -> Bound<OrdKey<K>>
+    where
+        K: Clone,
+    {
+        let prec = self.prec();
+        bound.map(|key| OrdKey {
+            key: self.pool.intern_existing(key.clone()),
+            prec,
+        })
+    }
+}
+
+impl<K, V> Extend<(K, V)> for ApproxBTreeMap<K, V>
+where
+    K: ApproxInternable + ApproxOrd,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for ApproxBTreeMap<K, V>
+where
+    K: ApproxInternable + ApproxOrd,
+{
+    /// Constructs a map using [`Precision::default()`] from an iterator of
+    /// key-value pairs.
+    ///
+    /// If the iterator produces any pairs with approximately equal keys, all
+    /// but one of the corresponding values will be dropped.
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> IntoIterator for ApproxBTreeMap<K, V> {
+    type Item = (K, V);
+
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.map.into_iter().map(|(k, v)| (k.key, v)),
+        }
+    }
+}
+impl<'a, K, V> IntoIterator for &'a ApproxBTreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, K, V> IntoIterator for &'a mut ApproxBTreeMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+iterator_structs! {
+    /// An owning iterator over the entries of an `ApproxBTreeMap`.
+    pub struct IntoIter<K, V>(btree_map::IntoIter<OrdKey<K>, V>) -> (K, V);
+
+    /// An owning iterator over the keys of an `ApproxBTreeMap`.
+    pub struct IntoKeys<K, V>(btree_map::IntoKeys<OrdKey<K>, V>) -> K;
+
+    /// An owning iterator over the values of an `ApproxBTreeMap`.
+    pub struct IntoValues<K, V>(btree_map::IntoValues<OrdKey<K>, V>) -> V;
+
+    /// An iterator over the entries of an `ApproxBTreeMap`.
+    pub struct Iter<'a, K, V>(btree_map::Iter<'a, OrdKey<K>, V>) -> (&'a K, &'a V);
+
+    /// A mutable iterator over the entries of an `ApproxBTreeMap`.
+    pub struct IterMut<'a, K, V>(btree_map::IterMut<'a, OrdKey<K>, V>) -> (&'a K, &'a mut V);
+
+    /// An iterator over the keys of an `ApproxBTreeMap`.
+    pub struct Keys<'a, K, V>(btree_map::Keys<'a, OrdKey<K>, V>) -> &'a K;
+
+    /// An iterator over the values of an `ApproxBTreeMap`.
+    pub struct Values<'a, K, V>(btree_map::Values<'a, OrdKey<K>, V>) -> &'a V;
+
+    /// A mutable iterator over the values of an `ApproxBTreeMap`.
+    pub struct ValuesMut<'a, K, V>(btree_map::ValuesMut<'a, OrdKey<K>, V>) -> &'a mut V;
+
+    /// An iterator over a range of entries of an `ApproxBTreeMap`.
+    pub struct Range<'a, K, V>(btree_map::Range<'a, OrdKey<K>, V>) -> (&'a K, &'a V);
+
+    /// A mutable iterator over a range of entries of an `ApproxBTreeMap`.
+    pub struct RangeMut<'a, K, V>(btree_map::RangeMut<'a, OrdKey<K>, V>) -> (&'a K, &'a mut V);
+}
+impl_exact_size_for_iterator_structs!(
+    IntoIter,
+    IntoKeys,
+    IntoValues,
+    Iter<'_>,
+    IterMut<'_>,
+    Keys<'_>,
+    Values<'_>,
+    ValuesMut<'_>,
+);
+impl_clone_for_iterator_structs!(Iter, Keys, Values, Range);
+
+/// A cursor pointing at a gap between two entries of an `ApproxBTreeMap`.
+///
+/// This `struct` is created by the [`lower_bound`] and [`upper_bound`] methods
+/// on [`ApproxBTreeMap`].
+///
+/// [`lower_bound`]: ApproxBTreeMap::lower_bound
+/// [`upper_bound`]: ApproxBTreeMap::upper_bound
+pub struct Cursor<'a, K, V> {
+    map: &'a BTreeMap<OrdKey<K>, V>,
+    /// Key of the entry before the gap, or `None` if the gap is at the start
+    /// of the map.
+    prev: Option<&'a OrdKey<K>>,
+}
+
+impl<K, V> Clone for Cursor<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map,
+            prev: self.prev,
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Cursor<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor")
+            .field("prev", &self.prev.map(|k| &k.key))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, K: ApproxOrd, V> Cursor<'a, K, V> {
+    /// Returns the entry after the cursor without moving the cursor, or `None`
+    /// if the cursor is at the end of the map.
+    pub fn peek_next(&self) -> Option<(&'a K, &'a V)> {
+        let (k, v) = match self.prev {
+            Some(prev) => self
+                .map
+                .range((Bound::Excluded(prev), Bound::Unbounded))
+                .next(),
+            None => self.map.first_key_value(),
+        }?;
+        Some((&k.key, v))
+    }
+    /// Returns the entry before the cursor without moving the cursor, or
+    /// `None` if the cursor is at the start of the map.
+    pub fn peek_prev(&self) -> Option<(&'a K, &'a V)> {
+        let (k, v) = self.map.get_key_value(self.prev?)?;
+        Some((&k.key, v))
+    }
+
+    /// Moves the cursor past the next entry and returns it, or returns `None`
+    /// without moving the cursor if it is at the end of the map.
+    #[allow(clippy::should_implement_trait)] // a cursor can move both ways
+    pub fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let (k, v) = match self.prev {
+            Some(prev) => self
+                .map
+                .range((Bound::Excluded(prev), Bound::Unbounded))
+                .next(),
+            None => self.map.first_key_value(),
+        }?;
+        self.prev = Some(k);
+        Some((&k.key, v))
+    }
+    /// Moves the cursor before the previous entry and returns it, or returns
+    /// `None` without moving the cursor if it is at the start of the map.
+    pub fn prev(&mut self) -> Option<(&'a K, &'a V)> {
+        let (k, v) = self.map.get_key_value(self.prev?)?;
+        self.prev = self.map.range(..k).next_back().map(|(k, _v)| k);
+        Some((&k.key, v))
+    }
+}
+
+/// A view into a single entry in a map, which may either be vacant or occupied.
+///
+/// This `enum` is constructed from the [`entry`] method on [`ApproxBTreeMap`].
+///
+/// [`entry`]: ApproxBTreeMap::entry
+pub enum Entry<'a, K, V> {
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// A vacant entry.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: ApproxOrd, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default if empty, and
+    /// returns a mutable reference to the value in the entry.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the default
+    /// function if empty, and returns a mutable reference to the value in the
+    /// entry.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting, if empty, the result of
+    /// the default function. This method allows for generating key-derived
+    /// values for insertion by providing the default function a reference to
+    /// the key that was moved during the `.entry(key)` method call.
+    pub fn or_insert_with_key<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce(&K) -> V,
+    {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let value = default(e.key());
+                e.insert(value)
+            }
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any
+    /// potential inserts into the map.
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            Entry::Occupied(mut e) => {
+                f(e.get_mut());
+                Entry::Occupied(e)
+            }
+            Entry::Vacant(e) => Entry::Vacant(e),
+        }
+    }
+}
+
+impl<'a, K: ApproxOrd, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if empty,
+    /// and returns a mutable reference to the value in the entry.
+    pub fn or_default(self) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(Default::default()),
+        }
+    }
+}
+
+/// A view into an occupied entry in an `ApproxBTreeMap`. It is part of the
+/// [`Entry`] enum.
+pub struct OccupiedEntry<'a, K, V> {
+    inner: btree_map::OccupiedEntry<'a, OrdKey<K>, V>,
+}
+
+impl<'a, K: ApproxOrd, V> OccupiedEntry<'a, K, V> {
+    /// Gets a reference to the key in the entry.
+    pub fn key(&self) -> &K {
+        &self.inner.key().key
+    }
+
+    /// Take the ownership of the key and value from the map.
+    pub fn remove_entry(self) -> (K, V) {
+        let (k, v) = self.inner.remove_entry();
+        (k.key, v)
+    }
+
+    /// Gets a reference to the value in the entry.
+    pub fn get(&self) -> &V {
+        self.inner.get()
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    ///
+    /// If you need a reference to the `OccupiedEntry` which may outlive the
+    /// destruction of the `Entry` value, see [`into_mut`].
+    ///
+    /// [`into_mut`]: Self::into_mut
+    pub fn get_mut(&mut self) -> &mut V {
+        self.inner.get_mut()
+    }
+
+    /// Converts the `OccupiedEntry` into a mutable reference to the value in
+    /// the entry with a lifetime bound to the map itself.
+    ///
+    /// If you need multiple references to the `OccupiedEntry`, see [`get_mut`].
+    ///
+    /// [`get_mut`]: Self::get_mut
+    pub fn into_mut(self) -> &'a mut V {
+        self.inner.into_mut()
+    }
+
+    /// Sets the value of the entry, and returns the entry's old value.
+    pub fn insert(&mut self, value: V) -> V {
+        self.inner.insert(value)
+    }
+
+    /// Takes the value out of the entry, and returns it.
+    pub fn remove(self) -> V {
+        self.inner.remove()
+    }
+}
+
+/// A view into a vacant entry in an `ApproxBTreeMap`. It is part of the
+/// [`Entry`] enum.
+pub struct VacantEntry<'a, K, V> {
+    inner: btree_map::VacantEntry<'a, OrdKey<K>, V>,
+}
+
+impl<'a, K: ApproxOrd, V> VacantEntry<'a, K, V> {
+    /// Gets a reference to the key that would be used when inserting a value
+    /// through the `VacantEntry`.
+    pub fn key(&self) -> &K {
+        &self.inner.key().key
+    }
+
+    /// Take ownership of the key.
+    pub fn into_key(self) -> K {
+        self.inner.into_key().key
+    }
+
+    /// Sets the value of the entry with the `VacantEntry`'s key, and returns a
+    /// mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.inner.insert(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn test_approx_btree_map() {
+        let mut map = ApproxBTreeMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(1.0, 'a');
+        map.insert(-2.0, 'b');
+        map.insert(0.5, 'c');
+        assert_eq!(map.insert(1.01, 'd'), Some('a'));
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(0.99), Some(&'d'));
+        assert_eq!(map.get(3.0), None);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [-2.0, 0.5, 1.0]);
+        assert_eq!(map.first_key_value(), Some((&-2.0, &'b')));
+        assert_eq!(map.last_key_value(), Some((&1.0, &'d')));
+
+        *map.entry(0.49).or_insert('x') = 'e';
+        map.entry(3.0).or_insert('f');
+        assert_eq!(
+            map.values().copied().collect::<Vec<_>>(),
+            ['b', 'e', 'd', 'f']
+        );
+
+        assert_eq!(map.remove(-2.01), Some('b'));
+        assert_eq!(map.pop_last(), Some((3.0, 'f')));
+        assert_eq!(map.pop_first(), Some((0.5, 'e')));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_approx_btree_map_range() {
+        let mut map = ApproxBTreeMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.extend((0..10).map(|i| (i as f64 * 0.5, i)));
+
+        let range = |r: (Bound<f64>, Bound<f64>)| map.range(r).map(|(_, &v)| v).collect::<Vec<_>>();
+        use Bound::*;
+        assert_eq!(range((Included(1.0), Included(2.0))), [2, 3, 4]);
+        assert_eq!(range((Included(1.01), Included(1.99))), [2, 3, 4]);
+        assert_eq!(range((Excluded(0.99), Excluded(2.01))), [3]);
+        // 1.2 is approximately equal to 1.0
+        assert_eq!(range((Included(1.2), Excluded(1.8))), [2, 3]);
+        // 1.3 is not approximately equal to anything in the map
+        assert_eq!(range((Excluded(1.3), Excluded(1.8))), [3]);
+        assert_eq!(range((Included(-7.0), Included(0.2))), [0]);
+        assert_eq!(range((Unbounded, Excluded(1.0))), [0, 1]);
+        assert_eq!(range((Excluded(100.0), Unbounded)), Vec::<i32>::new());
+        assert_eq!(map.range(3.9..).next_back(), Some((&4.5, &9)));
+    }
+
+    #[test]
+    fn test_approx_btree_map_cursor() {
+        let mut map = ApproxBTreeMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.extend([(1.0, 'a'), (2.0, 'b'), (3.0, 'c')]);
+
+        let mut cursor = map.lower_bound(Bound::Included(&2.01));
+        assert_eq!(cursor.peek_prev(), Some((&1.0, &'a')));
+        assert_eq!(cursor.peek_next(), Some((&2.0, &'b')));
+        assert_eq!(cursor.next(), Some((&2.0, &'b')));
+        assert_eq!(cursor.next(), Some((&3.0, &'c')));
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.prev(), Some((&3.0, &'c')));
+
+        let cursor = map.lower_bound(Bound::Excluded(&2.0));
+        assert_eq!(cursor.peek_next(), Some((&3.0, &'c')));
+        let mut cursor = map.upper_bound(Bound::Included(&1.99));
+        assert_eq!(cursor.peek_prev(), Some((&2.0, &'b')));
+        let cursor2 = map.upper_bound(Bound::Excluded(&2.0));
+        assert_eq!(cursor2.peek_prev(), Some((&1.0, &'a')));
+
+        assert_eq!(cursor.prev(), Some((&2.0, &'b')));
+        assert_eq!(cursor.prev(), Some((&1.0, &'a')));
+        assert_eq!(cursor.prev(), None);
+        assert_eq!(cursor.peek_next(), Some((&1.0, &'a')));
+        assert_eq!(map.upper_bound(Bound::Unbounded).peek_next(), None);
+    }
+
+    #[test]
+    fn test_approx_btree_map_split_off_append() {
+        let mut map = ApproxBTreeMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.extend((0..6).map(|i| (i as f64, i)));
+
+        let mut upper = map.split_off(&2.99);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0.0, 1.0, 2.0]);
+        assert_eq!(upper.keys().copied().collect::<Vec<_>>(), [3.0, 4.0, 5.0]);
+
+        let mut other = ApproxBTreeMap::new(Precision::absolute(3));
+        other.extend([(2.01, 20), (6.0, 6)]);
+        upper.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(upper.len(), 5);
+        // `upper` has a copy of the pool from `map`, which contains 2.0
+        assert_eq!(upper.first_key_value(), Some((&2.0, &20)));
+
+        map.append(&mut upper);
+        assert_eq!(map.len(), 7);
+        assert_eq!(map.get(2.0), Some(&20));
+        assert_eq!(
+            map.keys().copied().collect::<Vec<_>>(),
+            [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+        );
+    }
+}
@@ -0,0 +1,537 @@
+//! Ordered set that works for [`ApproxOrd`]ered values.
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::{FusedIterator, Peekable};
+use core::ops::RangeBounds;
+
+use crate::btree_map::{self, ApproxBTreeMap};
+use crate::{ApproxInternable, ApproxOrd, FloatPool, Precision};
+
+/// Approximate ordered set for objects with floating-point values, ordered
+/// using [`ApproxOrd`].
+///
+/// Values are interned using a [`FloatPool`] before they are inserted, so
+/// approximately equal values collapse into one. See [`ApproxBTreeMap`] for
+/// details.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxBTreeSet, Precision};
+///
+/// let mut angles = ApproxBTreeSet::new(Precision::DEFAULT);
+/// angles.insert(0.5);
+/// angles.insert(0.1 + 0.2);
+/// assert!(!angles.insert(0.3));
+///
+/// assert_eq!(angles.iter().copied().collect::<Vec<_>>(), [0.1 + 0.2, 0.5]);
+/// ```
+#[derive(Clone)]
+pub struct ApproxBTreeSet<K> {
+    map: ApproxBTreeMap<K, ()>,
+}
+
+impl<K> Default for ApproxBTreeSet<K> {
+    /// Constructs an empty set using [`Precision::default()`].
+    fn default() -> Self {
+        Self::new(Precision::default())
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for ApproxBTreeSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K> ApproxBTreeSet<K> {
+    /// Constructs an empty set.
+    pub fn new(prec: Precision) -> Self {
+        Self {
+            map: ApproxBTreeMap::new(prec),
+        }
+    }
+
+    /// Returns an iterator of all the values in the set, in sorted order.
+    pub fn iter(&self) -> Iter<'_, K> {
+        Iter {
+            inner: self.map.keys(),
+        }
+    }
+
+    /// Returns the number of values in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Clears the set, removing all values. Keeps the interned floats.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns a reference to the set's [`FloatPool`].
+    pub fn float_pool(&self) -> &FloatPool {
+        self.map.float_pool()
+    }
+
+    /// Returns the precision used to compare floats.
+    pub fn prec(&self) -> Precision {
+        self.map.prec()
+    }
+}
+
+impl<K> ApproxBTreeSet<K>
+where
+    K: ApproxInternable + ApproxOrd,
+{
+    /// Constructs an `ApproxBTreeSet<K>` from an iterator of values.
+    ///
+    /// If the iterator produces any approximately equal values, all but one
+    /// of them will be dropped.
+    pub fn from_iter<T: IntoIterator<Item = K>>(prec: Precision, iter: T) -> Self {
+        let mut set = Self::new(prec);
+        set.extend(iter);
+        set
+    }
+
+    /// Adds a value to the set, and returns whether it was newly inserted.
+    ///
+    /// If the set already contains an approximately equal value, the set is
+    /// not modified.
+    pub fn insert(&mut self, value: K) -> bool {
+        match self.map.entry(value) {
+            btree_map::Entry::Occupied(_) => false,
+            btree_map::Entry::Vacant(e) => {
+                e.insert(());
+                true
+            }
+        }
+    }
+    /// Returns whether the set contains a value.
+    pub fn contains(&self, value: K) -> bool {
+        self.map.contains_key(value)
+    }
+    /// Returns the value in the set that is approximately equal to the given
+    /// value, if any.
+    pub fn get(&self, value: K) -> Option<&K> {
+        Some(self.map.get_key_value(value)?.0)
+    }
+    /// Removes a value from the set, and returns whether it was present.
+    pub fn remove(&mut self, value: K) -> bool {
+        self.map.remove(value).is_some()
+    }
+    /// Removes and returns the value in the set that is approximately equal to
+    /// the given value, if any.
+    pub fn take(&mut self, value: K) -> Option<K> {
+        Some(self.map.remove_entry(value)?.0)
+    }
+
+    /// Returns the first value in the set, which is the minimum.
+    pub fn first(&self) -> Option<&K> {
+        Some(self.map.first_key_value()?.0)
+    }
+    /// Returns the last value in the set, which is the maximum.
+    pub fn last(&self) -> Option<&K> {
+        Some(self.map.last_key_value()?.0)
+    }
+    /// Removes and returns the first value in the set, which is the minimum.
+    pub fn pop_first(&mut self) -> Option<K> {
+        Some(self.map.pop_first()?.0)
+    }
+    /// Removes and returns the last value in the set, which is the maximum.
+    pub fn pop_last(&mut self) -> Option<K> {
+        Some(self.map.pop_last()?.0)
+    }
+
+    /// Retains only the values for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K) -> bool,
+    {
+        self.map.retain(|k, ()| f(k));
+    }
+
+    /// Returns an iterator over the values in `range`, in sorted order.
+    ///
+    /// See [`ApproxBTreeMap::range()`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if they are
+    /// approximately equal and both excluded.
+    pub fn range<R>(&self, range: R) -> Range<'_, K>
+    where
+        K: Clone,
+        R: RangeBounds<K>,
+    {
+        Range {
+            inner: self.map.range(range),
+        }
+    }
+
+    /// Moves all values from `other` into `self`, leaving `other` empty.
+    ///
+    /// Values from `other` are interned using the [`FloatPool`] of `self`.
+    pub fn append(&mut self, other: &mut Self) {
+        self.map.append(&mut other.map);
+    }
+    /// Splits the set in two at `value`. Returns a new set containing all the
+    /// values greater than or approximately equal to `value`, which are
+    /// removed from `self`.
+    ///
+    /// The new set starts with a copy of the [`FloatPool`] of `self`.
+    pub fn split_off(&mut self, value: &K) -> Self
+    where
+        K: Clone,
+    {
+        Self {
+            map: self.map.split_off(value),
+        }
+    }
+
+    /// Returns an iterator over the values that are in `self` or `other`, in
+    /// sorted order.
+    ///
+    /// Values are compared using the precision of `self`. If a value in `self`
+    /// is approximately equal to a value in `other`, only the one in `self` is
+    /// yielded.
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, K> {
+        Union {
+            inner: MergeIter::new(self, other),
+        }
+    }
+    /// Returns an iterator over the values that are in both `self` and
+    /// `other`, in sorted order.
+    ///
+    /// Values are compared using the precision of `self`, and the values in
+    /// `self` are yielded.
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, K> {
+        Intersection {
+            inner: MergeIter::new(self, other),
+        }
+    }
+    /// Returns an iterator over the values that are in `self` but not `other`,
+    /// in sorted order.
+    ///
+    /// Values are compared using the precision of `self`.
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, K> {
+        Difference {
+            inner: MergeIter::new(self, other),
+        }
+    }
+    /// Returns an iterator over the values that are in `self` or `other` but
+    /// not both, in sorted order.
+    ///
+    /// Values are compared using the precision of `self`.
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, K> {
+        SymmetricDifference {
+            inner: MergeIter::new(self, other),
+        }
+    }
+}
+
+impl<K> Extend<K> for ApproxBTreeSet<K>
+where
+    K: ApproxInternable + ApproxOrd,
+{
+    fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<K> FromIterator<K> for ApproxBTreeSet<K>
+where
+    K: ApproxInternable + ApproxOrd,
+{
+    /// Constructs a set using [`Precision::default()`] from an iterator of
+    /// values.
+    ///
+    /// If the iterator produces any approximately equal values, all but one
+    /// of them will be dropped.
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K> IntoIterator for ApproxBTreeSet<K> {
+    type Item = K;
+
+    type IntoIter = IntoIter<K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.map.into_keys(),
+        }
+    }
+}
+impl<'a, K> IntoIterator for &'a ApproxBTreeSet<K> {
+    type Item = &'a K;
+
+    type IntoIter = Iter<'a, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of an `ApproxBTreeSet`.
+#[derive(Debug)]
+pub struct Iter<'a, K> {
+    inner: btree_map::Keys<'a, K, ()>,
+}
+impl<K> Clone for Iter<'_, K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<K> DoubleEndedIterator for Iter<'_, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+impl<K> ExactSizeIterator for Iter<'_, K> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl<K> FusedIterator for Iter<'_, K> {}
+impl<'a, K> Iterator for Iter<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An owning iterator over the values of an `ApproxBTreeSet`.
+#[derive(Debug)]
+pub struct IntoIter<K> {
+    inner: btree_map::IntoKeys<K, ()>,
+}
+impl<K> DoubleEndedIterator for IntoIter<K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+impl<K> ExactSizeIterator for IntoIter<K> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl<K> FusedIterator for IntoIter<K> {}
+impl<K> Iterator for IntoIter<K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An iterator over a range of values of an `ApproxBTreeSet`.
+#[derive(Debug)]
+pub struct Range<'a, K> {
+    inner: btree_map::Range<'a, K, ()>,
+}
+impl<K> Clone for Range<'_, K> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<K> DoubleEndedIterator for Range<'_, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some(self.inner.next_back()?.0)
+    }
+}
+impl<K> FusedIterator for Range<'_, K> {}
+impl<'a, K> Iterator for Range<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.inner.next()?.0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Iterator that merges the values of two sets in sorted order, pairing up
+/// values that are approximately equal.
+struct MergeIter<'a, K> {
+    a: Peekable<Iter<'a, K>>,
+    b: Peekable<Iter<'a, K>>,
+    prec: Precision,
+}
+impl<K> Clone for MergeIter<'_, K> {
+    fn clone(&self) -> Self {
+        Self {
+            a: self.a.clone(),
+            b: self.b.clone(),
+            prec: self.prec,
+        }
+    }
+}
+impl<K: fmt::Debug> fmt::Debug for MergeIter<'_, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MergeIter")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .finish_non_exhaustive()
+    }
+}
+impl<'a, K> MergeIter<'a, K> {
+    fn new(a: &'a ApproxBTreeSet<K>, b: &'a ApproxBTreeSet<K>) -> Self {
+        Self {
+            a: a.iter().peekable(),
+            b: b.iter().peekable(),
+            prec: a.prec(),
+        }
+    }
+}
+impl<'a, K: ApproxOrd> Iterator for MergeIter<'a, K> {
+    type Item = (Option<&'a K>, Option<&'a K>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ordering = match (self.a.peek(), self.b.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(a), Some(b)) => a.approx_cmp(b, self.prec),
+        };
+        Some(match ordering {
+            Ordering::Less => (self.a.next(), None),
+            Ordering::Equal => (self.a.next(), self.b.next()),
+            Ordering::Greater => (None, self.b.next()),
+        })
+    }
+}
+
+macro_rules! set_operation_iterators {
+    (
+        $(
+            $(#[$attr:meta])*
+            $visibility:vis struct $struct_name:ident with |$a:pat_param, $b:pat_param| $f:expr;
+        )*
+    ) => {
+        $(
+            $(#[$attr])*
+            #[derive(Debug)]
+            $visibility struct $struct_name<'a, K> {
+                inner: MergeIter<'a, K>,
+            }
+            impl<K> Clone for $struct_name<'_, K> {
+                fn clone(&self) -> Self {
+                    Self {
+                        inner: self.inner.clone(),
+                    }
+                }
+            }
+            impl<K: ApproxOrd> FusedIterator for $struct_name<'_, K> {}
+            impl<'a, K: ApproxOrd> Iterator for $struct_name<'a, K> {
+                type Item = &'a K;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    self.inner.by_ref().find_map(|($a, $b)| $f)
+                }
+            }
+        )*
+    };
+}
+
+set_operation_iterators! {
+    /// An iterator over the union of two `ApproxBTreeSet`s.
+    pub struct Union with |a, b| a.or(b);
+
+    /// An iterator over the intersection of two `ApproxBTreeSet`s.
+    pub struct Intersection with |a, b| a.filter(|_| b.is_some());
+
+    /// An iterator over the difference of two `ApproxBTreeSet`s.
+    pub struct Difference with |a, b| a.filter(|_| b.is_none());
+
+    /// An iterator over the symmetric difference of two `ApproxBTreeSet`s.
+    pub struct SymmetricDifference with |a, b| match (a, b) {
+        (Some(_), Some(_)) => None,
+        (a, b) => a.or(b),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn test_approx_btree_set() {
+        let mut set = ApproxBTreeSet::new(Precision::absolute(3)); // bucket size = 0.125
+        assert!(set.insert(1.0));
+        assert!(set.insert(-1.0));
+        assert!(!set.insert(1.01));
+        assert!(set.contains(0.99));
+        assert_eq!(set.get(1.02), Some(&1.0));
+        assert_eq!(set.len(), 2);
+
+        set.extend([3.0, 2.0, 2.01]);
+        assert_eq!(
+            set.iter().copied().collect::<Vec<_>>(),
+            [-1.0, 1.0, 2.0, 3.0]
+        );
+        assert_eq!(set.range(0.99..2.01).copied().collect::<Vec<_>>(), [1.0]);
+        assert_eq!(set.first(), Some(&-1.0));
+        assert_eq!(set.last(), Some(&3.0));
+
+        let upper = set.split_off(&1.98);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), [-1.0, 1.0]);
+        assert_eq!(upper.into_iter().collect::<Vec<_>>(), [2.0, 3.0]);
+
+        assert!(set.remove(1.0));
+        assert_eq!(set.take(-0.99), Some(-1.0));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_approx_btree_set_operations() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let a = ApproxBTreeSet::from_iter(prec, [1.0, 2.0, 3.0, 4.0]);
+        let mut b = ApproxBTreeSet::from_iter(prec, [0.0, 2.01, 3.99, 5.0]);
+
+        let collect = |iter: &mut dyn Iterator<Item = &f64>| iter.copied().collect::<Vec<_>>();
+        assert_eq!(collect(&mut a.union(&b)), [0.0, 1.0, 2.0, 3.0, 4.0, 5.0],);
+        assert_eq!(collect(&mut a.intersection(&b)), [2.0, 4.0]);
+        assert_eq!(collect(&mut a.difference(&b)), [1.0, 3.0]);
+        assert_eq!(
+            collect(&mut a.symmetric_difference(&b)),
+            [0.0, 1.0, 3.0, 5.0]
+        );
+
+        let mut a = a;
+        a.append(&mut b);
+        assert!(b.is_empty());
+        assert_eq!(
+            a.iter().copied().collect::<Vec<_>>(),
+            [0.0, 1.0, 2.0, 3.0, 4.0, 5.0],
+        );
+    }
+}
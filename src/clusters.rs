@@ -0,0 +1,304 @@
+//! Transitive clustering of approximately equal values.
+
+use alloc::vec::{self, Vec};
+use core::hash::{BuildHasher, Hasher};
+use core::slice;
+
+use hashbrown::HashTable;
+use smallvec::SmallVec;
+
+use crate::hash_map::DefaultHashBuilder;
+use crate::{ApproxHash, Precision};
+
+/// Groups of values that are linked by chains of approximately equal values.
+///
+/// Approximate equality is not transitive, so interning values using a
+/// [`FloatPool`](crate::FloatPool) gives results that depend on the order of
+/// the values. `ApproxClusters` instead computes the transitive closure: two
+/// values are in the same cluster if every float in one is in the same bucket
+/// as, or a neighboring bucket of, the corresponding float in the other, or if
+/// they are linked by a chain of such values.
+///
+/// A cluster may therefore contain values that are not approximately equal to
+/// each other. Use [`Cluster::span()`] and [`Cluster::is_chained()`] to detect
+/// these.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxClusters, Precision};
+///
+/// let prec = Precision::absolute(3); // bucket size = 0.125
+/// let clusters = ApproxClusters::new(prec, [1.0, 5.0, 1.15, 1.3, 1.45]);
+///
+/// assert_eq!(clusters.len(), 2);
+/// assert_eq!(clusters[0].values(), [1.0, 1.15, 1.3, 1.45]);
+/// assert!(clusters[0].is_chained());
+/// assert_eq!(clusters[1].values(), [5.0]);
+/// assert!(!clusters[1].is_chained());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApproxClusters<K> {
+    clusters: Vec<Cluster<K>>,
+}
+
+impl<K> ApproxClusters<K> {
+    /// Returns an iterator over the clusters, in the order that their first
+    /// values appeared in the input.
+    pub fn iter(&self) -> slice::Iter<'_, Cluster<K>> {
+        self.clusters.iter()
+    }
+
+    /// Returns the number of clusters.
+    pub fn len(&self) -> usize {
+        self.clusters.len()
+    }
+
+    /// Returns whether there are no clusters.
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty()
+    }
+}
+
+impl<K: ApproxHash + Clone> ApproxClusters<K> {
+    /// Groups `values` into clusters of values that are linked by chains of
+    /// approximately equal values according to `prec`.
+    ///
+    /// Each distinct combination of buckets is checked against all
+    /// combinations of neighboring buckets, which is 3<sup>N</sup> lookups for
+    /// values containing N floats. This is fast for points and other small
+    /// values, but the cost grows exponentially with the number of floats.
+    pub fn new<I: IntoIterator<Item = K>>(prec: Precision, values: I) -> Self {
+        let hash_builder = DefaultHashBuilder::default();
+        let hash = |key: &K| {
+            let mut h = hash_builder.build_hasher();
+            key.interned_hash(&mut h);
+            h.finish()
+        };
+
+        // Sort the values into cells, where each cell has one bucket for each
+        // float.
+        let mut cells: Vec<K> = Vec::new();
+        let mut table: HashTable<usize> = HashTable::new();
+        let values: Vec<(K, usize)> = values
+            .into_iter()
+            .map(|value| {
+                let mut cell = value.clone();
+                cell.intern_floats(&mut |x| *x = f64::from_bits(prec.bucket(*x)));
+                let h = hash(&cell);
+                let cell_index = match table.find(h, |&i| cells[i].interned_eq(&cell)) {
+                    Some(&i) => i,
+                    None => {
+                        cells.push(cell);
+                        let i = cells.len() - 1;
+                        table.insert_unique(h, i, |&j| hash(&cells[j]));
+                        i
+                    }
+                };
+                (value, cell_index)
+            })
+            .collect();
+
+        // Link each cell to all its neighbors.
+        let mut parents: Vec<usize> = (0..cells.len()).collect();
+        for (i, cell) in cells.iter().enumerate() {
+            let candidates: SmallVec<[[Option<u64>; 3]; 4]> = floats(cell)
+                .into_iter()
+                .map(|x| {
+                    let (lo, mid, hi) = prec.nearby_buckets(x);
+                    [lo, Some(mid), hi]
+                })
+                .collect();
+            let mut choices: SmallVec<[usize; 4]> = SmallVec::from_elem(0, candidates.len());
+            loop {
+                let buckets: Option<SmallVec<[u64; 4]>> = core::iter::zip(&candidates, &choices)
+                    .map(|(c, &j)| c[j])
+                    .collect();
+                if let Some(buckets) = buckets {
+                    let mut neighbor = cell.clone();
+                    let mut buckets = buckets.into_iter();
+                    neighbor.intern_floats(&mut |x| {
+                        *x = buckets.next().map_or(*x, f64::from_bits);
+                    });
+                    let h = hash(&neighbor);
+                    if let Some(&j) = table.find(h, |&j| cells[j].interned_eq(&neighbor)) {
+                        union(&mut parents, i, j);
+                    }
+                }
+
+                // Advance to the next combination of neighboring buckets.
+                let Some(j) = choices.iter().position(|&c| c < 2) else {
+                    break;
+                };
+                choices[j] += 1;
+                choices[..j].fill(0);
+            }
+        }
+
+        // Collect the values in each cluster.
+        let mut cluster_of_root: Vec<Option<usize>> = alloc::vec![None; cells.len()];
+        let mut clusters: Vec<Vec<K>> = Vec::new();
+        for (value, cell_index) in values {
+            let root = find(&mut parents, cell_index);
+            let cluster_index = *cluster_of_root[root].get_or_insert_with(|| {
+                clusters.push(Vec::new());
+                clusters.len() - 1
+            });
+            clusters[cluster_index].push(value);
+        }
+
+        ApproxClusters {
+            clusters: clusters
+                .into_iter()
+                .map(|values| Cluster::new(prec, values))
+                .collect(),
+        }
+    }
+}
+
+impl<K> core::ops::Index<usize> for ApproxClusters<K> {
+    type Output = Cluster<K>;
+
+    fn index(&self, index: usize) -> &Cluster<K> {
+        &self.clusters[index]
+    }
+}
+
+impl<K> IntoIterator for ApproxClusters<K> {
+    type Item = Cluster<K>;
+
+    type IntoIter = vec::IntoIter<Cluster<K>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.clusters.into_iter()
+    }
+}
+impl<'a, K> IntoIterator for &'a ApproxClusters<K> {
+    type Item = &'a Cluster<K>;
+
+    type IntoIter = slice::Iter<'a, Cluster<K>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Group of values in [`ApproxClusters`].
+#[derive(Debug, Clone)]
+pub struct Cluster<K> {
+    values: Vec<K>,
+    span: f64,
+    is_chained: bool,
+}
+
+impl<K: ApproxHash + Clone> Cluster<K> {
+    fn new(prec: Precision, values: Vec<K>) -> Self {
+        let mut ranges: SmallVec<[(f64, f64); 4]> = SmallVec::new();
+        for value in &values {
+            let floats = floats(value);
+            ranges.resize(floats.len(), (f64::INFINITY, f64::NEG_INFINITY));
+            for (range, x) in core::iter::zip(&mut ranges, floats) {
+                *range = (range.0.min(x), range.1.max(x));
+            }
+        }
+        let span = ranges.iter().map(|(lo, hi)| hi - lo).fold(0.0, f64::max);
+        let is_chained = ranges.iter().any(|&(lo, hi)| !prec.f64_eq(lo, hi));
+        Cluster {
+            values,
+            span,
+            is_chained,
+        }
+    }
+}
+
+impl<K> Cluster<K> {
+    /// Returns the values in the cluster, in the order they appeared in the
+    /// input.
+    pub fn values(&self) -> &[K] {
+        &self.values
+    }
+    /// Returns the values in the cluster, in the order they appeared in the
+    /// input.
+    pub fn into_values(self) -> Vec<K> {
+        self.values
+    }
+
+    /// Returns the number of values in the cluster.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether the cluster is empty, which is never the case.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the greatest difference between corresponding floats of any
+    /// two values in the cluster.
+    pub fn span(&self) -> f64 {
+        self.span
+    }
+
+    /// Returns whether the cluster contains two values that are not
+    /// approximately equal, which means that they are only linked by a chain
+    /// of other values.
+    pub fn is_chained(&self) -> bool {
+        self.is_chained
+    }
+}
+
+/// Returns the floats in `value`.
+fn floats<K: ApproxHash + Clone>(value: &K) -> SmallVec<[f64; 4]> {
+    let mut ret = SmallVec::new();
+    value.clone().intern_floats(&mut |x| ret.push(*x));
+    ret
+}
+
+/// Returns the root of the tree containing `i` in a union-find forest.
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Merges the trees containing `i` and `j` in a union-find forest. The lower
+/// index becomes the root, so that the result does not depend on the order of
+/// the merges.
+fn union(parents: &mut [usize], i: usize, j: usize) {
+    let (i, j) = (find(parents, i), find(parents, j));
+    parents[i.max(j)] = i.min(j);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clusters() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let clusters = ApproxClusters::new(prec, [1.0, 5.0, 1.1, 3.0, 3.05, 5.3, 5.15]);
+        let values: Vec<&[f64]> = clusters.iter().map(|c| c.values()).collect();
+        assert_eq!(values, [&[1.0, 1.1][..], &[5.0, 5.3, 5.15], &[3.0, 3.05]]);
+        assert!(!clusters[0].is_chained());
+        assert!(clusters[1].is_chained());
+        assert!((clusters[1].span() - 0.3).abs() < 1e-9);
+
+        // The result does not depend on the order of the values.
+        let clusters = ApproxClusters::new(prec, [5.3, 1.0, 5.0, 5.15]);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].values(), [5.3, 5.0, 5.15]);
+    }
+
+    #[test]
+    fn test_clusters_of_points() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let points = [[0.0, 0.0], [0.1, 0.0], [0.2, 0.1], [0.2, 1.0], [0.0, 1.0]];
+        let clusters = ApproxClusters::new(prec, points);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].values(), [[0.0, 0.0], [0.1, 0.0], [0.2, 0.1]]);
+        assert_eq!(clusters[1].values(), [[0.2, 1.0], [0.0, 1.0]]);
+        assert!(clusters.iter().all(|c| !c.is_empty()));
+    }
+}
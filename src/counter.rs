@@ -0,0 +1,302 @@
+//! Counter for [`ApproxHash`]able values.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
+
+use crate::hash_map::{self, ApproxHashMap, DefaultHashBuilder};
+use crate::{ApproxHash, ApproxOrd, FloatPool, Precision};
+
+/// Counts how many times each distinct value occurs, where approximately
+/// equal values are counted together.
+///
+/// Values are interned using a [`FloatPool`], so the value that is stored for
+/// each count is the first one that was added.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxCounter, Precision};
+///
+/// let lengths = [1.0, 2.0, 0.1 + 0.2, 1.0, 0.3, 2.0, 1.0];
+/// let counter = ApproxCounter::from_iter(Precision::DEFAULT, lengths);
+///
+/// assert_eq!(counter.count(1.0), 3);
+/// assert_eq!(counter.most_common(1), [(&1.0, 3)]);
+/// assert_eq!(counter.histogram(), [(&(0.1 + 0.2), 2), (&1.0, 3), (&2.0, 2)]);
+/// ```
+#[derive(Clone)]
+pub struct ApproxCounter<K, S = DefaultHashBuilder> {
+    map: ApproxHashMap<K, usize, S>,
+    total: usize,
+}
+
+impl<K, S> fmt::Debug for ApproxCounter<K, S>
+where
+    K: fmt::Debug + ApproxOrd,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.map, f)
+    }
+}
+
+impl<K, S: Default> Default for ApproxCounter<K, S> {
+    /// Constructs an empty counter using [`Precision::default()`].
+    fn default() -> Self {
+        Self::with_hasher(S::default(), Precision::default())
+    }
+}
+
+impl<K> ApproxCounter<K, DefaultHashBuilder> {
+    /// Constructs an empty counter.
+    pub fn new(prec: Precision) -> ApproxCounter<K, DefaultHashBuilder> {
+        Self::with_hasher(DefaultHashBuilder::default(), prec)
+    }
+}
+
+impl<K, S> ApproxCounter<K, S> {
+    /// Constructs an empty counter which will use the given hash builder to
+    /// hash values.
+    pub fn with_hasher(hash_builder: S, prec: Precision) -> ApproxCounter<K, S> {
+        ApproxCounter {
+            map: ApproxHashMap::with_hasher(hash_builder, prec),
+            total: 0,
+        }
+    }
+
+    /// Returns an iterator of all the distinct values and their counts, in an
+    /// undefined order.
+    pub fn iter(&self) -> hash_map::Iter<'_, K, usize> {
+        self.map.iter()
+    }
+
+    /// Returns the number of distinct values.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns whether the counter is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the sum of all the counts.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the `n` values with the highest counts, from most common to
+    /// least common.
+    ///
+    /// Values with the same count are returned in an undefined order.
+    pub fn most_common(&self, n: usize) -> Vec<(&K, usize)> {
+        let mut ret: Vec<_> = self.map.iter().map(|(k, &count)| (k, count)).collect();
+        ret.sort_by(|(_, a), (_, b)| b.cmp(a));
+        ret.truncate(n);
+        ret
+    }
+
+    /// Clears the counter, removing all values. Keeps the interned floats.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.total = 0;
+    }
+
+    /// Returns a reference to the counter's [`FloatPool`].
+    pub fn float_pool(&self) -> &FloatPool {
+        self.map.float_pool()
+    }
+
+    /// Returns the precision used to compare floats.
+    pub fn prec(&self) -> Precision {
+        self.map.prec()
+    }
+}
+
+impl<K> ApproxCounter<K, DefaultHashBuilder>
+where
+    K: ApproxHash,
+{
+    /// Constructs an `ApproxCounter<K>` that counts the values from an
+    /// iterator.
+    pub fn from_iter<T: IntoIterator<Item = K>>(
+        prec: Precision,
+        iter: T,
+    ) -> ApproxCounter<K, DefaultHashBuilder> {
+        let mut counter = Self::new(prec);
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<K, S> ApproxCounter<K, S>
+where
+    K: ApproxHash,
+    S: BuildHasher,
+{
+    /// Adds one occurrence of a value, and returns its new count.
+    pub fn add(&mut self, key: K) -> usize {
+        self.add_n(key, 1)
+    }
+    /// Adds `n` occurrences of a value, and returns its new count.
+    ///
+    /// If `n` is zero and the value has not been added before, the counter is
+    /// not modified.
+    pub fn add_n(&mut self, key: K, n: usize) -> usize {
+        if n == 0 {
+            return self.count(key);
+        }
+        self.total += n;
+        let count = self.map.entry(key).or_insert(0);
+        *count += n;
+        *count
+    }
+    /// Returns the number of occurrences of a value (or something
+    /// approximately equal).
+    pub fn count(&self, key: K) -> usize {
+        self.map.get_owned(key).copied().unwrap_or(0)
+    }
+    /// Removes a value from the counter, and returns its count.
+    pub fn remove(&mut self, key: K) -> usize {
+        let count = self.map.remove(key).unwrap_or(0);
+        self.total -= count;
+        count
+    }
+    /// Adds all the counts from `other` to `self`.
+    ///
+    /// Values from `other` are interned using the [`FloatPool`] of `self`.
+    pub fn merge<S2>(&mut self, other: ApproxCounter<K, S2>) {
+        for (key, n) in other.map {
+            self.add_n(key, n);
+        }
+    }
+}
+
+impl<K, S> ApproxCounter<K, S>
+where
+    K: ApproxOrd,
+{
+    /// Returns all the distinct values and their counts, sorted by value.
+    ///
+    /// See [`ApproxHashMap::iter_sorted()`] for how values are compared.
+    pub fn histogram(&self) -> Vec<(&K, usize)> {
+        self.map
+            .iter_sorted()
+            .map(|(k, &count)| (k, count))
+            .collect()
+    }
+}
+
+impl<K, S> Extend<K> for ApproxCounter<K, S>
+where
+    K: ApproxHash,
+    S: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
+        for key in iter {
+            self.add(key);
+        }
+    }
+}
+
+impl<K, S> FromIterator<K> for ApproxCounter<K, S>
+where
+    K: ApproxHash,
+    S: BuildHasher + Default,
+{
+    /// Constructs a counter using [`Precision::default()`] that counts the
+    /// values from an iterator.
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let mut counter = Self::default();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<K, S> IntoIterator for ApproxCounter<K, S> {
+    type Item = (K, usize);
+
+    type IntoIter = hash_map::IntoIter<K, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+impl<'a, K, S> IntoIterator for &'a ApproxCounter<K, S> {
+    type Item = (&'a K, &'a usize);
+
+    type IntoIter = hash_map::Iter<'a, K, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut counter = ApproxCounter::from_iter(prec, [1.0, 2.0, 1.01, 0.99, 2.02, 5.0]);
+        assert_eq!(counter.count(1.0), 3);
+        assert_eq!(counter.count(2.0), 2);
+        assert_eq!(counter.count(3.0), 0);
+        assert_eq!(counter.total(), 6);
+        assert_eq!(counter.len(), 3);
+
+        assert_eq!(counter.add_n(5.01, 4), 5);
+        assert_eq!(counter.add_n(7.0, 0), 0);
+        assert_eq!(counter.len(), 3);
+        assert_eq!(counter.most_common(2), [(&5.0, 5), (&1.0, 3)]);
+        assert_eq!(counter.histogram(), [(&1.0, 3), (&2.0, 2), (&5.0, 5)]);
+
+        let other = ApproxCounter::from_iter(prec, [2.01, -1.0]);
+        counter.merge(other);
+        assert_eq!(
+            counter.histogram(),
+            [(&-1.0, 1), (&1.0, 3), (&2.0, 3), (&5.0, 5)]
+        );
+        assert_eq!(counter.total(), 12);
+
+        assert_eq!(counter.remove(4.99), 5);
+        assert_eq!(counter.total(), 7);
+    }
+
+    #[test]
+    fn test_collect() {
+        // `0.1 + 0.2 != 0.3`, but they are equal at the default precision.
+        let mut counter: ApproxCounter<f64> = [0.1 + 0.2, 0.3].into_iter().collect();
+        counter.extend([0.7 - 0.4]);
+        assert_eq!(counter.most_common(1), [(&(0.1 + 0.2), 3)]);
+    }
+
+    #[test]
+    fn test_merge_different_precisions() {
+        let mut counter = ApproxCounter::from_iter(Precision::absolute(3), [1.0, 2.0]);
+        // These are distinct at the finer precision, but not in `counter`.
+        let other = ApproxCounter::from_iter(Precision::absolute(10), [1.01, 0.99, 1.0, 3.0]);
+        assert_eq!(other.len(), 4);
+
+        counter.merge(other);
+        assert_eq!(counter.histogram(), [(&1.0, 4), (&2.0, 1), (&3.0, 1)]);
+        assert_eq!(counter.total(), 6);
+    }
+
+    #[test]
+    fn test_most_common_more_than_len() {
+        let counter = ApproxCounter::from_iter(Precision::absolute(3), [1.0, 2.0, 1.0]);
+        assert_eq!(counter.most_common(10), [(&1.0, 2), (&2.0, 1)]);
+        assert_eq!(counter.most_common(0), []);
+    }
+
+    #[test]
+    fn test_add_zero_to_existing() {
+        let mut counter = ApproxCounter::from_iter(Precision::absolute(3), [1.0, 1.0]);
+        assert_eq!(counter.add_n(1.01, 0), 2);
+        assert_eq!(counter.count(1.0), 2);
+        assert_eq!(counter.total(), 2);
+        assert_eq!(counter.len(), 1);
+    }
+}
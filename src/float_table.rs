@@ -0,0 +1,300 @@
+//! Interner that assigns a compact ID to each distinct float.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FusedIterator;
+
+use crate::pool::HashMap;
+use crate::{FloatPool, Precision};
+
+type IterInner<'a> = core::iter::Map<
+    core::iter::Enumerate<core::slice::Iter<'a, f64>>,
+    fn((usize, &'a f64)) -> (FloatId, f64),
+>;
+
+/// Handle to a float interned in a [`FloatTable`].
+///
+/// IDs are compared and hashed by their index, so they are cheap to store and
+/// compare. IDs from one table are not meaningful in another table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FloatId(u32);
+
+impl FloatId {
+    /// Returns the ID as a `u32`, which is its index in its table.
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the index of the ID in its table, for indexing into a slice.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Structure for interning similar floats based on approximate equality, which
+/// returns a [`FloatId`] for each distinct float instead of the float itself.
+///
+/// Floats are interned using a [`FloatPool`], so approximately equal floats are
+/// given the same ID. IDs are assigned in the order that floats are first
+/// interned, unless they are renumbered using [`FloatTable::sort_ids()`].
+///
+/// This is useful for storing a `u32` instead of an `f64` for each coordinate
+/// in memory-heavy workloads.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{FloatTable, Precision};
+///
+/// let mut table = FloatTable::new(Precision::DEFAULT);
+/// let a = table.intern(0.3);
+/// let b = table.intern(-1.0);
+/// assert_eq!(table.intern(0.1 + 0.2), a);
+/// assert_eq!(table.resolve(a), 0.3);
+/// assert_eq!(table.id_of(-1.0), Some(b));
+/// assert_eq!(table.id_of(2.0), None);
+///
+/// // Make IDs follow the numeric order of their values.
+/// let remap = table.sort_ids();
+/// let (a, b) = (remap[a.index()], remap[b.index()]);
+/// assert!(b < a);
+/// ```
+#[derive(Clone)]
+pub struct FloatTable {
+    pool: FloatPool,
+    /// Interned value for each ID.
+    floats: Vec<f64>,
+    /// ID for the bits of each interned value.
+    ids: HashMap<u64, FloatId>,
+    /// Whether IDs are in the same order as their values.
+    sorted: bool,
+}
+
+impl fmt::Debug for FloatTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FloatTable")
+            .field("prec", &self.prec())
+            .field("floats", &self.floats)
+            .finish()
+    }
+}
+
+impl Default for FloatTable {
+    /// Constructs an empty table using [`Precision::default()`].
+    fn default() -> Self {
+        Self::new(Precision::default())
+    }
+}
+
+impl FloatTable {
+    /// Constructs an empty table with the given precision.
+    pub fn new(prec: Precision) -> Self {
+        Self {
+            pool: FloatPool::new(prec),
+            floats: Vec::new(),
+            ids: HashMap::default(),
+            sorted: true,
+        }
+    }
+
+    /// Returns the precision level used by the table.
+    pub fn prec(&self) -> Precision {
+        self.pool.prec()
+    }
+
+    /// Returns a reference to the table's [`FloatPool`].
+    pub fn float_pool(&self) -> &FloatPool {
+        &self.pool
+    }
+
+    /// Returns the number of distinct floats in the table.
+    pub fn len(&self) -> usize {
+        self.floats.len()
+    }
+
+    /// Returns whether the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.floats.is_empty()
+    }
+
+    /// Interns `x` and returns its ID.
+    ///
+    /// If `x` is approximately equal to a float that has already been
+    /// interned, the existing ID is returned. Otherwise it is added to the
+    /// table with a new ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than `u32::MAX` distinct floats.
+    pub fn intern(&mut self, x: f64) -> FloatId {
+        let x = self.pool.intern(x);
+        *self.ids.entry(x.to_bits()).or_insert_with(|| {
+            let id =
+                FloatId(u32::try_from(self.floats.len()).expect("too many floats in FloatTable"));
+            if let Some(&last) = self.floats.last() {
+                self.sorted &= last < x;
+            }
+            self.floats.push(x);
+            id
+        })
+    }
+
+    /// Returns the ID of a float approximately equal to `x`, or `None` if
+    /// there is none.
+    pub fn id_of(&self, x: f64) -> Option<FloatId> {
+        let x = self.pool.try_intern(x)?;
+        self.ids.get(&x.to_bits()).copied()
+    }
+
+    /// Returns the interned float for an ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was not returned by this table.
+    pub fn resolve(&self, id: FloatId) -> f64 {
+        self.floats[id.index()]
+    }
+
+    /// Returns the interned float for an ID, or `None` if there is no such ID
+    /// in this table.
+    pub fn get(&self, id: FloatId) -> Option<f64> {
+        self.floats.get(id.index()).copied()
+    }
+
+    /// Compares the floats for two IDs numerically.
+    ///
+    /// If the IDs are sorted (see [`FloatTable::is_sorted()`]), this is the
+    /// same as comparing the IDs themselves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either ID was not returned by this table.
+    pub fn cmp_values(&self, a: FloatId, b: FloatId) -> Ordering {
+        self.resolve(a).total_cmp(&self.resolve(b))
+    }
+
+    /// Returns whether IDs are in the same order as the floats they refer to,
+    /// so that comparing IDs is the same as comparing their floats.
+    ///
+    /// This is true after [`FloatTable::sort_ids()`] until a float is interned
+    /// that is less than the greatest one in the table.
+    pub fn is_sorted(&self) -> bool {
+        self.sorted
+    }
+
+    /// Renumbers the IDs so that they are in the same order as the floats they
+    /// refer to, and returns the new ID for each old ID, indexed by
+    /// [`FloatId::index()`].
+    ///
+    /// IDs that were returned before this call must be replaced using the
+    /// returned vector.
+    pub fn sort_ids(&mut self) -> Vec<FloatId> {
+        let mut order: Vec<usize> = (0..self.floats.len()).collect();
+        order.sort_by(|&i, &j| self.floats[i].total_cmp(&self.floats[j]));
+
+        let mut remap = alloc::vec![FloatId(0); order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            remap[old] = FloatId(new as u32);
+        }
+        self.floats = order.iter().map(|&old| self.floats[old]).collect();
+        for id in self.ids.values_mut() {
+            *id = remap[id.index()];
+        }
+        self.sorted = true;
+        remap
+    }
+
+    /// Returns an iterator of all the IDs and floats in the table, in order of
+    /// ID.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: (self.floats.iter().enumerate()).map(|(i, &x)| (FloatId(i as u32), x)),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a FloatTable {
+    type Item = (FloatId, f64);
+
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the IDs and floats in a [`FloatTable`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    inner: IterInner<'a>,
+}
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+impl ExactSizeIterator for Iter<'_> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl FusedIterator for Iter<'_> {}
+impl Iterator for Iter<'_> {
+    type Item = (FloatId, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_float_table() {
+        let mut table = FloatTable::new(Precision::absolute(3)); // bucket size = 0.125
+        let a = table.intern(1.0);
+        let b = table.intern(2.0);
+        assert_eq!(table.intern(1.01), a);
+        assert_eq!(table.intern(0.0), FloatId(2));
+        assert_eq!(table.len(), 3);
+        assert!(!table.is_sorted());
+
+        assert_eq!(table.resolve(a), 1.0);
+        assert_eq!(table.get(b), Some(2.0));
+        assert_eq!(table.get(FloatId(3)), None);
+        assert_eq!(table.id_of(1.99), Some(b));
+        assert_eq!(table.id_of(0.01), Some(FloatId(2)));
+        assert_eq!(table.id_of(3.0), None);
+        assert_eq!(table.cmp_values(b, a), Ordering::Greater);
+
+        let ids: Vec<FloatId> = table.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, [a, b, FloatId(2)]);
+    }
+
+    #[test]
+    fn test_float_table_sort_ids() {
+        let mut table = FloatTable::new(Precision::absolute(3)); // bucket size = 0.125
+        let ids: Vec<FloatId> = [3.0, -1.0, 2.0, 0.5].map(|x| table.intern(x)).to_vec();
+        assert!(!table.is_sorted());
+
+        let remap = table.sort_ids();
+        assert!(table.is_sorted());
+        let ids: Vec<FloatId> = ids.iter().map(|id| remap[id.index()]).collect();
+        assert_eq!(ids, [FloatId(3), FloatId(0), FloatId(2), FloatId(1)]);
+        assert_eq!(table.resolve(ids[0]), 3.0);
+        assert_eq!(table.id_of(-0.99), Some(ids[1]));
+        assert!(ids[1] < ids[3] && ids[3] < ids[2]);
+
+        table.intern(4.0);
+        assert!(table.is_sorted());
+        table.intern(-2.0);
+        assert!(!table.is_sorted());
+    }
+}
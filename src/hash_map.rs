@@ -1,60 +1,80 @@
 //! Hash map that works for [`ApproxHash`]able values.
 
-use std::collections::{HashMap, hash_map};
-use std::fmt;
-use std::hash::{BuildHasher, BuildHasherDefault, Hasher, RandomState};
-use std::iter::FusedIterator;
-
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasher, Hasher};
+use core::iter::FusedIterator;
+use core::ops::Index;
+
+use hashbrown::{HashTable, hash_table};
 use smallvec::{SmallVec, smallvec};
 
-use crate::{ApproxHash, FloatPool, Precision};
-
-type IterInner<'a, K, V> = std::iter::Flatten<hash_map::Values<'a, u64, LinearApproxMap<K, V>>>;
-type IterMutInner<'a, K, V> =
-    std::iter::Flatten<hash_map::ValuesMut<'a, u64, LinearApproxMap<K, V>>>;
-type IntoIterInner<K, V> = std::iter::Flatten<hash_map::IntoValues<u64, LinearApproxMap<K, V>>>;
-
-#[derive(Debug, Default, Copy, Clone)]
-struct TrivialHasher(u64);
-
-impl Hasher for TrivialHasher {
-    fn finish(&self) -> u64 {
-        self.0
-    }
-
-    fn write(&mut self, _bytes: &[u8]) {
-        unreachable!("TrivialHasher can only be used with write_u64()");
-    }
-
-    fn write_u64(&mut self, i: u64) {
-        self.0 = i;
-    }
-}
+use crate::precision::{MAX_ABSOLUTE, MAX_RELATIVE};
+use crate::{
+    ApproxBorrow, ApproxEq, ApproxHash, ApproxInternable, ApproxLookup, ApproxOrd, FloatPool,
+    Precision,
+};
+
+type IterInner<'a, K, V> = core::iter::Flatten<hash_table::Iter<'a, LinearApproxMap<K, V>>>;
+type IterMutInner<'a, K, V> = core::iter::Flatten<hash_table::IterMut<'a, LinearApproxMap<K, V>>>;
+type IntoIterInner<K, V> = core::iter::Flatten<hash_table::IntoIter<LinearApproxMap<K, V>>>;
+type DrainInner<'a, K, V> = core::iter::Flatten<hash_table::Drain<'a, LinearApproxMap<K, V>>>;
+
+/// Maximum radius, in buckets, searched by [`ApproxHashMap::nearest()`] before
+/// falling back to a linear scan.
+const NEAREST_MAX_RADIUS: u32 = 4;
+
+/// Precision used for sorting interned values, such as by
+/// [`ApproxHashMap::iter_sorted()`]. Interned floats are always at least two
+/// buckets apart at this precision.
+pub(crate) const SORT_PREC: Precision = Precision::new(MAX_ABSOLUTE, MAX_RELATIVE);
+
+/// Default hash builder for [`ApproxHashMap`].
+#[cfg(feature = "std")]
+pub type DefaultHashBuilder = std::hash::RandomState;
+/// Default hash builder for [`ApproxHashMap`].
+#[cfg(not(feature = "std"))]
+pub type DefaultHashBuilder = hashbrown::DefaultHashBuilder;
 
 /// Approximate hash map for objects with floating-point values, using a
-/// `BTreeMap` to record arbitrary hash values for floats.
+/// [`FloatPool`] to intern floats in keys so that they can be hashed exactly.
 #[derive(Clone)]
-pub struct ApproxHashMap<K, V, S = RandomState> {
+pub struct ApproxHashMap<K, V, S = DefaultHashBuilder> {
     hash_builder: S,
     pool: FloatPool,
-    map: HashMap<u64, LinearApproxMap<K, V>, BuildHasherDefault<TrivialHasher>>,
+    map: HashTable<LinearApproxMap<K, V>>,
     len: usize,
 }
 
 impl<K, V, S> fmt::Debug for ApproxHashMap<K, V, S>
 where
-    K: fmt::Debug,
+    K: fmt::Debug + ApproxOrd,
     V: fmt::Debug,
 {
+    /// Formats the entries of the map, sorted by key using
+    /// [`ApproxHashMap::iter_sorted()`] so that the output does not depend on
+    /// the hasher.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_map().entries(self.iter()).finish()
+        f.debug_map().entries(self.iter_sorted()).finish()
     }
 }
 
-impl<K, V> ApproxHashMap<K, V, RandomState> {
+impl<K, V, S: Default> Default for ApproxHashMap<K, V, S> {
+    /// Constructs an empty map using [`Precision::default()`].
+    fn default() -> Self {
+        Self::with_hasher(S::default(), Precision::default())
+    }
+}
+
+impl<K, V> ApproxHashMap<K, V, DefaultHashBuilder> {
     /// Constructs an empty map.
-    pub fn new(prec: Precision) -> ApproxHashMap<K, V, RandomState> {
-        Self::with_hasher(RandomState::default(), prec)
+    pub fn new(prec: Precision) -> ApproxHashMap<K, V, DefaultHashBuilder> {
+        Self::with_hasher(DefaultHashBuilder::default(), prec)
+    }
+    /// Constructs an empty map with space for at least `capacity` entries.
+    pub fn with_capacity(capacity: usize, prec: Precision) -> ApproxHashMap<K, V> {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default(), prec)
     }
 }
 
@@ -62,10 +82,30 @@ impl<K, V, S> ApproxHashMap<K, V, S> {
     /// Constructs an empty map which will use the given hash builder to hash
     /// keys.
     pub fn with_hasher(hash_builder: S, prec: Precision) -> ApproxHashMap<K, V, S> {
+        Self::with_hasher_and_float_pool(hash_builder, FloatPool::new(prec))
+    }
+    /// Constructs an empty map with space for at least `capacity` entries,
+    /// which will use the given hash builder to hash keys.
+    pub fn with_capacity_and_hasher(
+        capacity: usize,
+        hash_builder: S,
+        prec: Precision,
+    ) -> ApproxHashMap<K, V, S> {
+        ApproxHashMap {
+            map: HashTable::with_capacity(capacity),
+            ..Self::with_hasher(hash_builder, prec)
+        }
+    }
+    /// Constructs an empty map which will use the given hash builder to hash
+    /// keys and intern floats using an existing pool.
+    pub(crate) fn with_hasher_and_float_pool(
+        hash_builder: S,
+        pool: FloatPool,
+    ) -> ApproxHashMap<K, V, S> {
         ApproxHashMap {
             hash_builder,
-            pool: FloatPool::new(prec),
-            map: HashMap::default(),
+            pool,
+            map: HashTable::new(),
             len: 0,
         }
     }
@@ -74,14 +114,14 @@ impl<K, V, S> ApproxHashMap<K, V, S> {
     pub fn iter(&self) -> Iter<'_, K, V> {
         Iter {
             len: self.len,
-            inner: self.map.values().flatten().map(|(k, v)| (k, v)),
+            inner: self.map.iter().flatten().map(|(k, v)| (k, v)),
         }
     }
     /// Returns an iterator of mutable references to all the entries in the map.
     pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
         IterMut {
             len: self.len,
-            inner: self.map.values_mut().flatten().map(|(k, v)| (k, v)),
+            inner: self.map.iter_mut().flatten().map(|(k, v)| (k, v)),
         }
     }
 
@@ -89,14 +129,14 @@ impl<K, V, S> ApproxHashMap<K, V, S> {
     pub fn keys<'a>(&'a self) -> Keys<'a, K, V> {
         Keys {
             len: self.len,
-            inner: self.map.values().flatten().map(|(_k, v)| v),
+            inner: self.map.iter().flatten().map(|(k, _v)| k),
         }
     }
     /// Converts the map into an iterator of all its keys.
     pub fn into_keys(self) -> IntoKeys<K, V> {
         IntoKeys {
             len: self.len,
-            inner: self.map.into_values().flatten().map(|(k, _v)| k),
+            inner: self.map.into_iter().flatten().map(|(k, _v)| k),
         }
     }
 
@@ -104,21 +144,21 @@ impl<K, V, S> ApproxHashMap<K, V, S> {
     pub fn values(&self) -> Values<'_, K, V> {
         Values {
             len: self.len,
-            inner: self.map.values().flatten().map(|(_k, v)| v),
+            inner: self.map.iter().flatten().map(|(_k, v)| v),
         }
     }
     /// Returns an iterator of mutable references to all the values in the map.
     pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
         ValuesMut {
             len: self.len,
-            inner: self.map.values_mut().flatten().map(|(_k, v)| v),
+            inner: self.map.iter_mut().flatten().map(|(_k, v)| v),
         }
     }
     /// Converts the map into an iterator of all its values.
     pub fn into_values(self) -> IntoValues<K, V> {
         IntoValues {
             len: self.len,
-            inner: self.map.into_values().flatten().map(|(_k, v)| v),
+            inner: self.map.into_iter().flatten().map(|(_k, v)| v),
         }
     }
 
@@ -129,13 +169,77 @@ impl<K, V, S> ApproxHashMap<K, V, S> {
 
     /// Returns whether the map is empty.
     pub fn is_empty(&self) -> bool {
-        self.map.is_empty()
+        self.len == 0
+    }
+
+    /// Returns the number of distinct key hashes the map can hold without
+    /// reallocating.
+    ///
+    /// This is a lower bound on the number of entries the map can hold without
+    /// reallocating.
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+    /// Reserves capacity for at least `additional` more entries.
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional, |m| m.hash);
+    }
+    /// Shrinks the capacity of the map as much as possible. Keeps the interned
+    /// floats.
+    pub fn shrink_to_fit(&mut self) {
+        self.map.shrink_to_fit(|m| m.hash);
     }
 
     /// Clears the map, removing all key-value pairs. Keeps the allocated memory
     /// and keeps the interned floats.
     pub fn clear(&mut self) {
         self.map.clear();
+        self.len = 0;
+    }
+
+    /// Retains only the entries for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let mut removed = 0;
+        self.map.retain(|m| {
+            let old_len = m.len();
+            m.entries.retain(|(k, v)| f(k, v));
+            removed += old_len - m.len();
+            m.len() > 0
+        });
+        self.len -= removed;
+    }
+
+    /// Clears the map, returning all key-value pairs as an iterator. Keeps the
+    /// allocated memory and keeps the interned floats.
+    ///
+    /// If the returned iterator is dropped before being fully consumed, the
+    /// remaining entries are dropped.
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        Drain {
+            len: core::mem::take(&mut self.len),
+            inner: self.map.drain().flatten(),
+        }
+    }
+
+    /// Returns an iterator that removes and yields the entries for which `f`
+    /// returns `true`.
+    ///
+    /// If the returned iterator is dropped before being fully consumed, the
+    /// remaining entries are kept in the map.
+    pub fn extract_if<F>(&mut self, f: F) -> ExtractIf<'_, K, V, F>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf {
+            map: &mut self.map,
+            len: &mut self.len,
+            bucket: 0,
+            index: 0,
+            f,
+        }
     }
 
     /// Returns a reference to the map's [`BuildHasher`].
@@ -154,7 +258,7 @@ impl<K, V, S> ApproxHashMap<K, V, S> {
     }
 }
 
-impl<K, V> ApproxHashMap<K, V, RandomState>
+impl<K, V> ApproxHashMap<K, V, DefaultHashBuilder>
 where
     K: ApproxHash,
 {
@@ -170,7 +274,12 @@ where
         map.extend(iter);
         map
     }
+}
 
+impl<K, V, S> ApproxHashMap<K, V, S>
+where
+    K: ApproxHash,
+{
     /// Replaces all floats in `key` with interned ones that are approximately
     /// equal, returning a mutated copy of `key`.
     ///
@@ -197,6 +306,28 @@ where
     pub fn try_intern(&self, key: K) -> Option<K> {
         self.pool.try_intern(key)
     }
+
+    /// Removes floats from the map's [`FloatPool`] that are not used by any key
+    /// in the map, such as after many keys have been removed.
+    ///
+    /// Keys in the map are unaffected and can still be looked up. Queries that
+    /// were approximately equal to a removed key may resolve to a different key
+    /// afterward.
+    pub fn compact_float_pool(&mut self) {
+        let mut used: hashbrown::HashSet<u64> = hashbrown::HashSet::new();
+        for linear_map in self.map.iter_mut() {
+            for (k, _) in &mut linear_map.entries {
+                // This does not modify the key. The float in the pool is marked
+                // as well, since it may differ from the float in the key for
+                // types such as `f32`.
+                k.intern_floats(&mut |x| {
+                    used.insert(x.to_bits());
+                    used.extend(self.pool.get(*x).map(f64::to_bits));
+                });
+            }
+        }
+        self.pool.retain(|x| used.contains(&x.to_bits()));
+    }
 }
 
 impl<K, V, S> ApproxHashMap<K, V, S>
@@ -207,7 +338,7 @@ where
     /// Returns an entry in the map for in-place manipulation.
     pub fn entry(&mut self, mut key: K) -> Entry<'_, K, V> {
         let hash = self.intern_and_hash(&mut key);
-        self.entry_with_interned_key(key, hash)
+        self.entry_interned(hash, key)
     }
     /// Returns an entry in the map for in-place manipulation.
     ///
@@ -217,67 +348,68 @@ where
         K: Clone,
     {
         let hash = self.intern_and_hash(key);
-        self.entry_with_interned_key(key.clone(), hash)
-    }
-    fn entry_with_interned_key(&mut self, key: K, hash: u64) -> Entry<'_, K, V> {
-        match self.map.entry(hash) {
-            hash_map::Entry::Occupied(e) => match e.get().index_of(&key) {
-                Some(index) => Entry::Occupied(OccupiedEntry {
-                    hash_map_entry: e,
-                    index,
-                    len: &mut self.len,
-                }),
-                None => Entry::Vacant(VacantEntry {
-                    hash_map_entry: hash_map::Entry::Occupied(e),
-                    key,
-                    len: &mut self.len,
-                }),
-            },
-            hash_map_entry @ hash_map::Entry::Vacant(_) => Entry::Vacant(VacantEntry {
-                hash_map_entry,
-                key,
-                len: &mut self.len,
-            }),
-        }
+        self.entry_interned(hash, key.clone())
     }
     /// Returns the value in the map associated to the given key (or something
     /// approximately equal).
-    pub fn get(&self, key: K) -> Option<&V> {
+    ///
+    /// The key may be any borrowed form of the map's key type, such as `[f64]`
+    /// for a map with `Vec<f64>` keys.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: ApproxBorrow<Q>,
+        Q: ?Sized + ApproxLookup,
+    {
         Some(self.get_key_value(key)?.1)
     }
     /// Returns the existing key-value pair that corresponds to the given key,
     /// or `None` if it is not present.
-    pub fn get_key_value(&self, key: K) -> Option<(&K, &V)> {
+    ///
+    /// The key may be any borrowed form of the map's key type, such as `[f64]`
+    /// for a map with `Vec<f64>` keys.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: ApproxBorrow<Q>,
+        Q: ?Sized + ApproxLookup,
+    {
         // Early exit optimization; don't bother hashing
         if self.is_empty() {
             return None;
         }
 
-        let (key, hash) = self.try_intern_and_hash(key)?;
-        let linear_map = self.map.get(&hash)?;
-        let index = linear_map.index_of(&key)?;
-        let (k, v) = linear_map.key_value(index);
+        let mut f = |x| self.pool.get(x);
+        let mut h = self.hash_builder.build_hasher();
+        key.lookup_hash(&mut f, &mut h)?;
+        let hash = h.finish();
+        let linear_map = self.map.find(hash, |m| m.hash == hash)?;
+        let (k, v) = linear_map
+            .entries
+            .iter()
+            .find(|(k, _)| key.lookup_eq(k.borrow(), &mut f))?;
         Some((k, v))
     }
     /// Returns whether the map contains a key.
-    pub fn contains_key(&self, key: K) -> bool {
+    ///
+    /// The key may be any borrowed form of the map's key type, such as `[f64]`
+    /// for a map with `Vec<f64>` keys.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: ApproxBorrow<Q>,
+        Q: ?Sized + ApproxLookup,
+    {
         self.get(key).is_some()
     }
     /// Returns a mutable reference to the value corresponding to a key.
     pub fn get_mut(&mut self, mut key: K) -> Option<&mut V> {
         let hash = self.intern_and_hash(&mut key);
-        let linear_map = self.map.get_mut(&hash)?;
-        let index = linear_map.index_of(&key)?;
-        Some(linear_map.value_mut(index))
+        self.get_mut_interned(hash, &key)
     }
     /// Returns a mutable reference to the value corresponding to a key.
     ///
     /// `key` is interned in-place.
     pub fn get_mut_with_mut_key(&mut self, key: &mut K) -> Option<&mut V> {
         let hash = self.intern_and_hash(key);
-        let linear_map = self.map.get_mut(&hash)?;
-        let index = linear_map.index_of(key)?;
-        Some(linear_map.value_mut(index))
+        self.get_mut_interned(hash, key)
     }
     /// Inserts an entry into the map and returns the old value, if any.
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
@@ -313,25 +445,379 @@ where
     /// if the key was not present.
     pub fn remove_entry(&mut self, mut key: K) -> Option<(K, V)> {
         let hash = self.intern_and_hash(&mut key);
-        let linear_map = self.map.get_mut(&hash)?;
-        let index = linear_map.index_of(&key)?;
-        Some(linear_map.remove(index))
+        match self.entry_interned(hash, key) {
+            Entry::Occupied(e) => Some(e.remove_entry()),
+            Entry::Vacant(_) => None,
+        }
     }
+    /// Returns mutable references to the values corresponding to several keys
+    /// at once. Each reference is `None` if the key is not present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any two keys are approximately equal.
+    pub fn get_disjoint_mut<const N: usize>(&mut self, keys: [K; N]) -> [Option<&mut V>; N] {
+        // Floats that are not in the map's pool are interned using a separate
+        // pool, so that approximately equal keys are detected even if they are
+        // not present, without adding them to the map's pool.
+        let mut scratch = FloatPool::new(self.prec());
+        let keys = keys.map(|mut key| {
+            key.intern_floats(&mut |x| {
+                *x = match self.pool.get(*x) {
+                    Some(saved) => saved,
+                    None => scratch.insert(*x).0,
+                };
+            });
+            key
+        });
+        for i in 0..N {
+            for j in 0..i {
+                assert!(!keys[i].interned_eq(&keys[j]), "duplicate keys");
+            }
+        }
 
-    fn try_intern_and_hash(&self, key: K) -> Option<(K, u64)> {
-        let key = self.pool.try_intern(key)?;
+        let hashes = keys.each_ref().map(|key| self.hash_interned(key));
+        // Only look up each hash once, since `HashTable::get_disjoint_mut()`
+        // requires the results to be distinct.
+        let is_first_with_hash = |i: usize| !hashes[..i].contains(&hashes[i]);
+        let linear_maps = self
+            .map
+            .get_disjoint_mut(hashes, |i, m| m.hash == hashes[i] && is_first_with_hash(i));
+
+        let mut ret = [const { None }; N];
+        for linear_map in linear_maps.into_iter().flatten() {
+            for (k, v) in &mut linear_map.entries {
+                let matching_key =
+                    (0..N).find(|&i| hashes[i] == linear_map.hash && keys[i].interned_eq(k));
+                if let Some(i) = matching_key {
+                    ret[i] = Some(v);
+                }
+            }
+        }
+        ret
+    }
+    /// Returns the value corresponding to a key, inserting the result of
+    /// `default` if it is not present, and returns references to the key and
+    /// value in the map.
+    pub fn find_or_insert_with<F>(&mut self, key: K, default: F) -> (&K, &mut V)
+    where
+        F: FnOnce() -> V,
+    {
+        match self.entry(key) {
+            Entry::Occupied(e) => e.into_key_value(),
+            Entry::Vacant(e) => e.insert_entry(default()).into_key_value(),
+        }
+    }
+
+    /// Returns the hash of a key that has already been interned using this
+    /// map's [`FloatPool`], such as a key returned by
+    /// [`ApproxHashMap::intern()`].
+    ///
+    /// This can be combined with the other `*_interned()` methods to avoid
+    /// interning and hashing the same key more than once.
+    pub fn hash_interned(&self, key: &K) -> u64 {
         let mut h = self.hash_builder.build_hasher();
         key.interned_hash(&mut h);
-        let hash = h.finish();
+        h.finish()
+    }
+    /// Returns the existing key-value pair that corresponds to a key that has
+    /// already been interned using this map's [`FloatPool`], given its hash
+    /// from [`ApproxHashMap::hash_interned()`].
+    ///
+    /// If `key` is not interned or `hash` is not its hash, this may return
+    /// `None` even if the map contains an approximately equal key.
+    pub fn get_key_value_interned(&self, hash: u64, key: &K) -> Option<(&K, &V)> {
+        let linear_map = self.map.find(hash, |m| m.hash == hash)?;
+        let index = linear_map.index_of(key)?;
+        let (k, v) = linear_map.key_value(index);
+        Some((k, v))
+    }
+    /// Returns a mutable reference to the value corresponding to a key that
+    /// has already been interned using this map's [`FloatPool`], given its
+    /// hash from [`ApproxHashMap::hash_interned()`].
+    ///
+    /// If `key` is not interned or `hash` is not its hash, this may return
+    /// `None` even if the map contains an approximately equal key.
+    pub fn get_mut_interned(&mut self, hash: u64, key: &K) -> Option<&mut V> {
+        let linear_map = self.map.find_mut(hash, |m| m.hash == hash)?;
+        let index = linear_map.index_of(key)?;
+        Some(linear_map.value_mut(index))
+    }
+    /// Returns an entry in the map for in-place manipulation, given a key that
+    /// has already been interned using this map's [`FloatPool`] and its hash
+    /// from [`ApproxHashMap::hash_interned()`].
+    ///
+    /// If `key` is not interned or `hash` is not its hash, the map may end up
+    /// with multiple approximately equal keys.
+    pub fn entry_interned(&mut self, hash: u64, key: K) -> Entry<'_, K, V> {
+        match self.map.entry(hash, |m| m.hash == hash, |m| m.hash) {
+            hash_table::Entry::Occupied(e) => match e.get().index_of(&key) {
+                Some(index) => Entry::Occupied(OccupiedEntry {
+                    table_entry: e,
+                    index,
+                    len: &mut self.len,
+                }),
+                None => Entry::Vacant(VacantEntry {
+                    table_entry: hash_table::Entry::Occupied(e),
+                    hash,
+                    key,
+                    len: &mut self.len,
+                }),
+            },
+            table_entry @ hash_table::Entry::Vacant(_) => Entry::Vacant(VacantEntry {
+                table_entry,
+                hash,
+                key,
+                len: &mut self.len,
+            }),
+        }
+    }
+
+    /// Returns a reference to the value corresponding to a key, or `None` if
+    /// it is not present.
+    ///
+    /// Unlike [`ApproxHashMap::get()`], this takes the key by value and does
+    /// not require `K: ApproxLookup`, so it works for any [`ApproxHash`] key.
+    pub fn get_owned(&self, key: K) -> Option<&V> {
+        Some(self.get_key_value_owned(key)?.1)
+    }
+    /// Returns the existing key-value pair that corresponds to the given key,
+    /// or `None` if it is not present.
+    ///
+    /// Unlike [`ApproxHashMap::get_key_value()`], this takes the key by value
+    /// and does not require `K: ApproxLookup`, so it works for any
+    /// [`ApproxHash`] key.
+    pub fn get_key_value_owned(&self, key: K) -> Option<(&K, &V)> {
+        // Early exit optimization; don't bother hashing
+        if self.is_empty() {
+            return None;
+        }
+
+        let (key, hash) = self.try_intern_and_hash(key)?;
+        self.get_key_value_interned(hash, &key)
+    }
+    /// Returns whether the map contains a key.
+    ///
+    /// Unlike [`ApproxHashMap::contains_key()`], this takes the key by value
+    /// and does not require `K: ApproxLookup`, so it works for any
+    /// [`ApproxHash`] key.
+    pub fn contains_key_owned(&self, key: K) -> bool {
+        self.get_key_value_owned(key).is_some()
+    }
+
+    fn try_intern_and_hash(&self, key: K) -> Option<(K, u64)> {
+        let key = self.pool.try_intern(key)?;
+        let hash = self.hash_interned(&key);
         Some((key, hash))
     }
     fn intern_and_hash(&mut self, key: &mut K) -> u64 {
         self.pool.intern_in_place(key);
-        let mut h = self.hash_builder.build_hasher();
-        key.interned_hash(&mut h);
-        h.finish()
+        self.hash_interned(key)
+    }
+}
+impl<K, V, S> ApproxHashMap<K, V, S>
+where
+    K: ApproxHash + Clone,
+    S: BuildHasher,
+{
+    /// Returns an iterator over the entries whose keys are the same as `key`
+    /// except that each float may be in a bucket up to `radius` buckets away
+    /// from the bucket containing the corresponding float in `key`.
+    ///
+    /// The entry returned by [`ApproxHashMap::get_key_value()`] is always
+    /// yielded, if any, and with `radius = 0` it is the only one. Entries are
+    /// yielded approximately in order of increasing distance from `key`.
+    ///
+    /// The number of candidate keys grows exponentially with the number of
+    /// floats in `key`, so this is best suited to scalar and small-vector keys.
+    pub fn get_within(&self, key: K, radius: u32) -> Within<'_, K, V, S> {
+        let mut candidates = vec![];
+        key.clone()
+            .intern_floats(&mut |x| candidates.push(self.pool.get_within(*x, radius)));
+        Within {
+            map: self,
+            indices: vec![0; candidates.len()],
+            done: candidates.iter().any(|c| c.is_empty()),
+            candidates,
+            key,
+        }
+    }
+
+    /// Returns the entry whose key is nearest to `key`, or `None` if the map
+    /// is empty.
+    ///
+    /// Distance between keys is the greatest absolute difference between
+    /// corresponding floats. Keys within a few buckets are found using
+    /// [`ApproxHashMap::get_within()`] with increasing radius, and once any are
+    /// found, one more bucket is searched in each direction since a key there
+    /// may still be nearer. If there are none, every entry in the map is
+    /// checked.
+    ///
+    /// If the bucket size differs between the floats in `key` and the keys
+    /// around it, such as with [`Precision::relative()`] near a power of two,
+    /// the result may not be exactly the nearest key.
+    pub fn nearest(&self, key: K) -> Option<(&K, &V)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let Some(radius) = (0..=NEAREST_MAX_RADIUS)
+            .find(|&radius| self.get_within(key.clone(), radius).next().is_some())
+        else {
+            return closest_to(&key, self.iter());
+        };
+        // The key found may be up to `radius + 1` buckets away, or 1 bucket
+        // away with `radius = 0`. Any nearer key is at most one bucket further.
+        closest_to(&key, self.get_within(key.clone(), radius.max(1) + 1))
+    }
+}
+
+/// Returns the entry whose key has the smallest [`float_distance()`] to `key`.
+fn closest_to<'a, K, V>(
+    key: &K,
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+) -> Option<(&'a K, &'a V)>
+where
+    K: ApproxInternable + Clone,
+{
+    entries
+        .filter_map(|(k, v)| Some((float_distance(key, k)?, (k, v))))
+        .min_by(|(d1, _), (d2, _)| d1.total_cmp(d2))
+        .map(|(_, kv)| kv)
+}
+
+/// Returns the greatest absolute difference between corresponding floats in
+/// `a` and `b`, or `None` if they do not have the same number of floats.
+fn float_distance<K: ApproxInternable + Clone>(a: &K, b: &K) -> Option<f64> {
+    let floats = |k: &K| {
+        let mut ret = SmallVec::<[f64; 4]>::new();
+        k.clone().intern_floats(&mut |x| ret.push(*x));
+        ret
+    };
+    let (a, b) = (floats(a), floats(b));
+    (a.len() == b.len()).then(|| {
+        core::iter::zip(a, b)
+            .map(|(x, y)| (x - y).abs())
+            .fold(0.0, f64::max)
+    })
+}
+
+impl<K, V, S> ApproxHashMap<K, V, S>
+where
+    K: ApproxOrd,
+{
+    /// Returns a vector of all the entries in the map, sorted by key.
+    ///
+    /// Keys are compared using [`ApproxOrd`] at the finest possible precision,
+    /// so distinct keys in the map are never considered equal.
+    pub fn iter_sorted(&self) -> vec::IntoIter<(&K, &V)> {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_by(|(k1, _), (k2, _)| k1.approx_cmp(k2, SORT_PREC));
+        entries.into_iter()
+    }
+}
+
+impl<K, Q, V, S> Index<&Q> for ApproxHashMap<K, V, S>
+where
+    K: ApproxBorrow<Q>,
+    Q: ?Sized + ApproxLookup,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the map.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in ApproxHashMap")
+    }
+}
+
+impl<K, V, S> PartialEq for ApproxHashMap<K, V, S>
+where
+    K: ApproxHash + ApproxLookup,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    /// Returns whether there is a one-to-one correspondence between the keys
+    /// of both maps such that corresponding values are equal.
+    ///
+    /// Keys in `self` are matched using the [`FloatPool`] of `other`.
+    fn eq(&self, other: &Self) -> bool {
+        other.corresponds_to(self, |a, b| a == b)
+    }
+}
+
+impl<K, V, S> ApproxEq for ApproxHashMap<K, V, S>
+where
+    K: ApproxHash + ApproxLookup + ApproxOrd + fmt::Debug,
+    V: ApproxEq,
+    S: BuildHasher,
+{
+    /// Returns whether there is a one-to-one correspondence between the keys
+    /// of both maps such that corresponding values are approximately equal
+    /// according to `prec`.
+    ///
+    /// Keys in `other` are matched using the [`FloatPool`] of `self`, so the
+    /// maps may use different pools.
+    fn approx_eq(&self, other: &Self, prec: Precision) -> bool {
+        self.corresponds_to(other, |a, b| b.approx_eq(a, prec))
+    }
+}
+
+impl<K, V, S> ApproxHashMap<K, V, S>
+where
+    K: ApproxHash + ApproxLookup,
+    S: BuildHasher,
+{
+    /// Returns whether both maps have the same number of entries and each key
+    /// in `other` matches a different key in `self` such that `values_eq`
+    /// returns `true` for their values.
+    ///
+    /// Keys in `other` are matched using the [`FloatPool`] of `self`.
+    fn corresponds_to<V2, S2>(
+        &self,
+        other: &ApproxHashMap<K, V2, S2>,
+        mut values_eq: impl FnMut(&V, &V2) -> bool,
+    ) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        let mut matched_keys = Vec::with_capacity(self.len());
+        for (k, v) in other {
+            let Some((k2, v2)) = self.get_key_value(k) else {
+                return false;
+            };
+            if !values_eq(v2, v) {
+                return false;
+            }
+            matched_keys.push(core::ptr::from_ref(k2));
+        }
+        // Two keys in `other` could match the same key in `self`.
+        matched_keys.sort_unstable();
+        matched_keys.windows(2).all(|w| w[0] != w[1])
     }
 }
+
+impl<K, V, S> FromIterator<(K, V)> for ApproxHashMap<K, V, S>
+where
+    K: ApproxHash,
+    S: BuildHasher + Default,
+{
+    /// Constructs a map using [`Precision::default()`] from an iterator of
+    /// key-value pairs.
+    ///
+    /// If the iterator produces any pairs with approximately equal keys, all
+    /// but one of the corresponding values will be dropped.
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
 impl<K, V, S> IntoIterator for ApproxHashMap<K, V, S> {
     type Item = (K, V);
 
@@ -341,7 +827,7 @@ impl<K, V, S> IntoIterator for ApproxHashMap<K, V, S> {
         IntoIter {
             len: self.len,
             #[allow(clippy::map_identity)] // needed for full generality of the macro
-            inner: self.map.into_values().flatten().map(|kv| kv),
+            inner: self.map.into_iter().flatten().map(|kv| kv),
         }
     }
 }
@@ -364,7 +850,9 @@ impl<'a, K, V, S> IntoIterator for &'a mut ApproxHashMap<K, V, S> {
     }
 }
 
-macro_rules! iterator_structs {
+/// Like the crate's `iterator_structs!`, but for iterators that are not
+/// double-ended, so the structs count the remaining entries themselves.
+macro_rules! counted_iterator_structs {
     (
         $(
             $(#[$attr:meta])*
@@ -380,7 +868,7 @@ macro_rules! iterator_structs {
             #[derive(Debug)]
             $visibility struct $struct_name<$($lt,)? $K, $V> {
                 len: usize,
-                inner: std::iter::Map<
+                inner: core::iter::Map<
                     $inner_type,
                     fn(<$inner_type as Iterator>::Item) -> $output_type,
                 >,
@@ -411,7 +899,7 @@ macro_rules! iterator_structs {
     };
 }
 
-macro_rules! impl_clone_for_iterator_structs {
+macro_rules! impl_clone_for_counted_iterator_structs {
     ($($type:ident),* $(,)?) => {
         $(
             impl<'a, K, V> Clone for $type<'a, K, V> {
@@ -426,7 +914,7 @@ macro_rules! impl_clone_for_iterator_structs {
     };
 }
 
-iterator_structs! {
+counted_iterator_structs! {
     /// An owning iterator over the entries of an `ApproxHashMap`.
     pub struct IntoIter<K, V>(IntoIterInner<K, V>) -> (K, V);
 
@@ -443,7 +931,7 @@ iterator_structs! {
     pub struct IterMut<'a, K, V>(IterMutInner<'a ,K, V>) -> (&'a K, &'a mut V);
 
     /// An iterator over the keys of an `ApproxHashMap`.
-    pub struct Keys<'a, K, V>(IterInner<'a, K, V>) -> &'a V;
+    pub struct Keys<'a, K, V>(IterInner<'a, K, V>) -> &'a K;
 
     /// An iterator over the values of an `ApproxHashMap`.
     pub struct Values<'a, K, V>(IterInner<'a, K, V>) -> &'a V;
@@ -451,7 +939,151 @@ iterator_structs! {
     /// A mutable iterator over the values of an `ApproxHashMap`.
     pub struct ValuesMut<'a, K, V>(IterMutInner<'a, K, V>) -> &'a mut V;
 }
-impl_clone_for_iterator_structs!(Iter, Keys, Values);
+impl_clone_for_counted_iterator_structs!(Iter, Keys, Values);
+
+/// A draining iterator over the entries of an `ApproxHashMap`.
+///
+/// This `struct` is created by the [`drain`] method on [`ApproxHashMap`].
+///
+/// [`drain`]: ApproxHashMap::drain
+#[derive(Debug)]
+pub struct Drain<'a, K, V> {
+    len: usize,
+    inner: DrainInner<'a, K, V>,
+}
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<K, V> FusedIterator for Drain<'_, K, V> {}
+impl<K, V> Iterator for Drain<'_, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.len = self.len.saturating_sub(1);
+        self.inner.next()
+    }
+}
+
+/// An iterator over the entries of an `ApproxHashMap` that are near a key.
+///
+/// This `struct` is created by the [`get_within`] method on [`ApproxHashMap`].
+///
+/// [`get_within`]: ApproxHashMap::get_within
+pub struct Within<'a, K, V, S> {
+    map: &'a ApproxHashMap<K, V, S>,
+    key: K,
+    /// Interned candidates for each float in `key`.
+    candidates: Vec<Vec<f64>>,
+    /// Index into each list of candidates for the next key to check.
+    indices: Vec<usize>,
+    done: bool,
+}
+impl<K, V, S> fmt::Debug for Within<'_, K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Within").finish_non_exhaustive()
+    }
+}
+impl<K, V, S> FusedIterator for Within<'_, K, V, S>
+where
+    K: ApproxHash + Clone,
+    S: BuildHasher,
+{
+}
+impl<'a, K, V, S> Iterator for Within<'a, K, V, S>
+where
+    K: ApproxHash + Clone,
+    S: BuildHasher,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let mut key = self.key.clone();
+            let mut i = 0;
+            key.intern_floats(&mut |x| {
+                *x = self.candidates[i][self.indices[i]];
+                i += 1;
+            });
+
+            // Advance to the next combination of candidates, with the first
+            // float changing least often.
+            self.done = true;
+            for (index, candidates) in core::iter::zip(&mut self.indices, &self.candidates).rev() {
+                *index += 1;
+                if *index < candidates.len() {
+                    self.done = false;
+                    break;
+                }
+                *index = 0;
+            }
+
+            let hash = self.map.hash_interned(&key);
+            if let Some(kv) = self.map.get_key_value_interned(hash, &key) {
+                return Some(kv);
+            }
+        }
+        None
+    }
+}
+
+/// An iterator that removes and yields the entries of an `ApproxHashMap` that
+/// satisfy a predicate.
+///
+/// This `struct` is created by the [`extract_if`] method on [`ApproxHashMap`].
+///
+/// [`extract_if`]: ApproxHashMap::extract_if
+pub struct ExtractIf<'a, K, V, F> {
+    map: &'a mut HashTable<LinearApproxMap<K, V>>,
+    len: &'a mut usize,
+    /// Index of the current bucket in the hash table.
+    bucket: usize,
+    /// Index of the next entry to check in the current bucket.
+    index: usize,
+    f: F,
+}
+impl<K, V, F> fmt::Debug for ExtractIf<'_, K, V, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtractIf").finish_non_exhaustive()
+    }
+}
+impl<K, V, F> FusedIterator for ExtractIf<'_, K, V, F> where F: FnMut(&K, &mut V) -> bool {}
+impl<K, V, F> Iterator for ExtractIf<'_, K, V, F>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Removing from a `HashTable` never moves other elements, so bucket
+        // indices stay valid for the whole iteration.
+        while self.bucket < self.map.num_buckets() {
+            if let Ok(mut table_entry) = self.map.get_bucket_entry(self.bucket) {
+                let linear_map = table_entry.get_mut();
+                while self.index < linear_map.len() {
+                    let (k, v) = &mut linear_map.entries[self.index];
+                    if (self.f)(k, v) {
+                        *self.len -= 1;
+                        let kv = linear_map.remove(self.index);
+                        if linear_map.len() == 0 {
+                            table_entry.remove();
+                        }
+                        return Some(kv);
+                    }
+                    self.index += 1;
+                }
+            }
+            self.bucket += 1;
+            self.index = 0;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(*self.len))
+    }
+}
 
 /// A view into a single entry in a map, which may either be vacant or occupied.
 ///
@@ -557,7 +1189,7 @@ impl<'a, K, V: Default> Entry<'a, K, V> {
 /// A view into an occupied entry in an `ApproxHashMap`. It is part of the
 /// [`Entry`] enum.
 pub struct OccupiedEntry<'a, K, V> {
-    hash_map_entry: hash_map::OccupiedEntry<'a, u64, LinearApproxMap<K, V>>,
+    table_entry: hash_table::OccupiedEntry<'a, LinearApproxMap<K, V>>,
     index: usize,
     len: &'a mut usize,
 }
@@ -565,22 +1197,22 @@ pub struct OccupiedEntry<'a, K, V> {
 impl<'a, K, V> OccupiedEntry<'a, K, V> {
     /// Gets a reference to the key in the entry.
     pub fn key(&self) -> &K {
-        self.hash_map_entry.get().key(self.index)
+        self.table_entry.get().key(self.index)
     }
 
     /// Take the ownership of the key and value from the map.
     pub fn remove_entry(mut self) -> (K, V) {
         *self.len -= 1;
-        if self.hash_map_entry.get().len() == 1 {
-            self.hash_map_entry.remove_entry().1.unwrap_exactly_one()
+        if self.table_entry.get().len() == 1 {
+            self.table_entry.remove().0.unwrap_exactly_one()
         } else {
-            self.hash_map_entry.get_mut().remove(self.index)
+            self.table_entry.get_mut().remove(self.index)
         }
     }
 
     /// Gets a reference to the value in the entry.
     pub fn get(&self) -> &V {
-        self.hash_map_entry.get().value(self.index)
+        self.table_entry.get().value(self.index)
     }
 
     /// Gets a mutable reference to the value in the entry.
@@ -590,7 +1222,7 @@ impl<'a, K, V> OccupiedEntry<'a, K, V> {
     ///
     /// [`into_mut`]: Self::into_mut
     pub fn get_mut(&mut self) -> &mut V {
-        self.hash_map_entry.get_mut().value_mut(self.index)
+        self.table_entry.get_mut().value_mut(self.index)
     }
 
     /// Converts the `OccupiedEntry` into a mutable reference to the value in
@@ -600,12 +1232,19 @@ impl<'a, K, V> OccupiedEntry<'a, K, V> {
     ///
     /// [`get_mut`]: Self::get_mut
     pub fn into_mut(self) -> &'a mut V {
-        self.hash_map_entry.into_mut().value_mut(self.index)
+        self.table_entry.into_mut().value_mut(self.index)
+    }
+
+    /// Converts the `OccupiedEntry` into a reference to the key and a mutable
+    /// reference to the value in the entry with a lifetime bound to the map
+    /// itself.
+    pub fn into_key_value(self) -> (&'a K, &'a mut V) {
+        self.table_entry.into_mut().key_value_mut(self.index)
     }
 
     /// Sets the value of the entry, and returns the entry's old value.
     pub fn insert(&mut self, value: V) -> V {
-        std::mem::replace(self.get_mut(), value)
+        core::mem::replace(self.get_mut(), value)
     }
 
     /// Takes the value out of the entry, and returns it.
@@ -617,7 +1256,8 @@ impl<'a, K, V> OccupiedEntry<'a, K, V> {
 /// A view into a vacant entry in an `ApproxHashMap`. It is part of the
 /// [`Entry`] enum.
 pub struct VacantEntry<'a, K, V> {
-    hash_map_entry: hash_map::Entry<'a, u64, LinearApproxMap<K, V>>,
+    table_entry: hash_table::Entry<'a, LinearApproxMap<K, V>>,
+    hash: u64,
     key: K,
     len: &'a mut usize,
 }
@@ -644,20 +1284,20 @@ impl<'a, K, V> VacantEntry<'a, K, V> {
     /// `OccupiedEntry`.
     pub fn insert_entry(self, value: V) -> OccupiedEntry<'a, K, V> {
         *self.len += 1;
-        let (index, hash_map_entry);
-        match self.hash_map_entry {
-            hash_map::Entry::Occupied(mut e) => {
+        let (index, table_entry);
+        match self.table_entry {
+            hash_table::Entry::Occupied(mut e) => {
                 index = e.get_mut().push(self.key, value);
-                hash_map_entry = e;
+                table_entry = e;
             }
-            hash_map::Entry::Vacant(e) => {
+            hash_table::Entry::Vacant(e) => {
                 index = 0;
-                hash_map_entry =
-                    e.insert_entry(LinearApproxMap::new_with_single_entry(self.key, value));
+                let linear_map = LinearApproxMap::new_with_single_entry(self.hash, self.key, value);
+                table_entry = e.insert(linear_map);
             }
         };
         OccupiedEntry {
-            hash_map_entry,
+            table_entry,
             index,
             len: self.len,
         }
@@ -676,59 +1316,62 @@ where
     }
 }
 
+/// List of entries whose keys all have the same hash.
 #[derive(Debug, Clone)]
-struct LinearApproxMap<K, V>(SmallVec<[(K, V); 1]>);
-
-impl<K, V> Default for LinearApproxMap<K, V> {
-    fn default() -> Self {
-        Self(SmallVec::new())
-    }
+struct LinearApproxMap<K, V> {
+    hash: u64,
+    entries: SmallVec<[(K, V); 1]>,
 }
 
 impl<K, V> LinearApproxMap<K, V> {
-    fn new_with_single_entry(key: K, value: V) -> Self {
-        Self(smallvec![(key, value)])
+    fn new_with_single_entry(hash: u64, key: K, value: V) -> Self {
+        let entries = smallvec![(key, value)];
+        Self { hash, entries }
     }
 
     fn len(&self) -> usize {
-        self.0.len()
+        self.entries.len()
     }
 
     fn unwrap_exactly_one(self) -> (K, V) {
         let msg = "expected exactly one element";
         assert_eq!(self.len(), 1, "{msg}");
-        self.0.into_iter().next().expect(msg)
+        self.entries.into_iter().next().expect(msg)
     }
 }
 
 impl<K: ApproxHash, V> LinearApproxMap<K, V> {
     fn index_of(&self, key: &K) -> Option<usize> {
-        self.0.iter().position(|(k, _)| k.interned_eq(key))
+        self.entries.iter().position(|(k, _)| k.interned_eq(key))
     }
 }
 
 impl<K, V> LinearApproxMap<K, V> {
     fn key_value(&self, index: usize) -> &(K, V) {
-        &self.0[index]
+        &self.entries[index]
     }
     fn key(&self, index: usize) -> &K {
-        &self.0[index].0
+        &self.entries[index].0
     }
     fn value(&self, index: usize) -> &V {
-        &self.0[index].1
+        &self.entries[index].1
     }
 
     fn value_mut(&mut self, index: usize) -> &mut V {
-        &mut self.0[index].1
+        &mut self.entries[index].1
+    }
+    fn key_value_mut(&mut self, index: usize) -> (&K, &mut V) {
+        let (k, v) = &mut self.entries[index];
+        (k, v)
     }
 
     fn remove(&mut self, index: usize) -> (K, V) {
-        self.0.remove(index)
+        self.entries.remove(index)
     }
 
     fn push(&mut self, key: K, value: V) -> usize {
         let i = self.len();
-        self.0.push((key, value));
+        self.entries.push((key, value));
         i
     }
 }
@@ -739,17 +1382,18 @@ impl<K, V> IntoIterator for LinearApproxMap<K, V> {
     type IntoIter = smallvec::IntoIter<[(K, V); 1]>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.entries.into_iter()
     }
 }
 
 impl<'a, K, V> IntoIterator for &'a LinearApproxMap<K, V> {
     type Item = (&'a K, &'a V);
 
-    type IntoIter = std::iter::Map<std::slice::Iter<'a, (K, V)>, fn(&'a (K, V)) -> (&'a K, &'a V)>;
+    type IntoIter =
+        core::iter::Map<core::slice::Iter<'a, (K, V)>, fn(&'a (K, V)) -> (&'a K, &'a V)>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.iter().map(|(k, v)| (k, v))
+        self.entries.iter().map(|(k, v)| (k, v))
     }
 }
 
@@ -757,15 +1401,17 @@ impl<'a, K, V> IntoIterator for &'a mut LinearApproxMap<K, V> {
     type Item = (&'a K, &'a mut V);
 
     type IntoIter =
-        std::iter::Map<std::slice::IterMut<'a, (K, V)>, fn(&'a mut (K, V)) -> (&'a K, &'a mut V)>;
+        core::iter::Map<core::slice::IterMut<'a, (K, V)>, fn(&'a mut (K, V)) -> (&'a K, &'a mut V)>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.iter_mut().map(|(k, v)| (k, v))
+        self.entries.iter_mut().map(|(k, v)| (k, v))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use alloc::{boxed::Box, format, vec};
+
     use super::*;
 
     #[test]
@@ -776,12 +1422,322 @@ mod tests {
         map.insert([0.6, 0.2], 'c');
         map.insert([0.15, -3.0], 'd');
 
-        assert_eq!(map.get([-5.12, -3.0]), None);
-        assert_eq!(map.get([0.5, -3.0]), None);
-        assert_eq!(map.get([0.12, -3.0]), Some(&'d'));
-        assert_eq!(map.get([-0.12, -2.9]), Some(&'d'));
-        assert_eq!(map.get([-0.12, 2.9]), None);
-        assert_eq!(map.get([0.44, 5.0]), Some(&'b'));
-        assert_eq!(map.get([0.4, 0.3]), Some(&'c'));
+        assert_eq!(map.get(&[-5.12, -3.0]), None);
+        assert_eq!(map.get(&[0.5, -3.0]), None);
+        assert_eq!(map.get(&[0.12, -3.0]), Some(&'d'));
+        assert_eq!(map.get(&[-0.12, -2.9]), Some(&'d'));
+        assert_eq!(map.get(&[-0.12, 2.9]), None);
+        assert_eq!(map.get(&[0.44, 5.0]), Some(&'b'));
+        assert_eq!(map.get(&[0.4, 0.3]), Some(&'c'));
+    }
+
+    #[test]
+    fn test_interned_api() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert([0.1, -3.0], 'a');
+
+        let key = map.intern([0.12, -3.0]);
+        assert_eq!(key, *map.get_key_value(&[0.1, -3.0]).unwrap().0);
+        let hash = map.hash_interned(&key);
+        assert_eq!(map.get_key_value_interned(hash, &key), Some((&key, &'a')));
+        *map.get_mut_interned(hash, &key).unwrap() = 'b';
+        assert_eq!(map.get(&[0.1, -3.0]), Some(&'b'));
+
+        let key = map.intern([0.6, 0.2]);
+        let hash = map.hash_interned(&key);
+        assert!(map.get_key_value_interned(hash, &key).is_none());
+        map.entry_interned(hash, key).or_insert('c');
+        assert_eq!(map.get(&[0.58, 0.2]), Some(&'c'));
+        assert_eq!(map.len(), 2);
+
+        let (k, v) = map.find_or_insert_with([0.62, 0.2], || 'd');
+        assert_eq!((*k, *v), ([0.6, 0.2], 'c'));
+        let (k, v) = map.find_or_insert_with([5.0, 5.0], || 'd');
+        assert_eq!((*k, *v), ([5.0, 5.0], 'd'));
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn test_borrowed_lookup() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(vec![0.1, -3.0], 'a');
+        map.insert(vec![0.6], 'b');
+        map.insert(vec![], 'c');
+
+        assert_eq!(map.get(&[0.12, -3.0][..]), Some(&'a'));
+        assert_eq!(map.get(&[0.58][..]), Some(&'b'));
+        assert_eq!(map.get(&[][..]), Some(&'c'));
+        assert_eq!(map.get(&[0.6, 0.0][..]), None);
+        assert_eq!(map.get(&[0.1][..]), None);
+        assert_eq!(map.get(&[5.0][..]), None);
+        let (k, _) = map.get_key_value(&[0.62][..]).unwrap();
+        assert_eq!(k, map.get_key_value(&vec![0.6]).unwrap().0);
+
+        let mut map = ApproxHashMap::new(Precision::absolute(3));
+        map.insert(Box::new((1.0_f32, Some(2.0))), 'a');
+        assert!(map.contains_key(&(1.01_f32, Some(2.02))));
+        assert!(!map.contains_key(&(1.01_f32, None)));
+        assert!(map.contains_key(&Box::new((0.99_f32, Some(1.98)))));
+    }
+
+    #[test]
+    fn test_owned_lookup() {
+        /// Key that implements `ApproxHash` but not `ApproxLookup`.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        struct Point(f64, f64);
+        impl ApproxInternable for Point {
+            fn intern_floats<F: FnMut(&mut f64)>(&mut self, f: &mut F) {
+                f(&mut self.0);
+                f(&mut self.1);
+            }
+        }
+        impl ApproxHash for Point {
+            fn interned_eq(&self, other: &Self) -> bool {
+                self.0.interned_eq(&other.0) && self.1.interned_eq(&other.1)
+            }
+            fn interned_hash<H: Hasher>(&self, state: &mut H) {
+                self.0.interned_hash(state);
+                self.1.interned_hash(state);
+            }
+        }
+
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(Point(1.0, 2.0), 'a');
+        assert_eq!(map.get_owned(Point(1.01, 1.99)), Some(&'a'));
+        assert_eq!(
+            map.get_key_value_owned(Point(0.99, 2.0)),
+            Some((&Point(1.0, 2.0), &'a')),
+        );
+        assert!(map.contains_key_owned(Point(1.0, 2.01)));
+        assert!(!map.contains_key_owned(Point(2.0, 1.0)));
+        assert_eq!(map.get_owned(Point(3.0, 2.0)), None);
+    }
+
+    #[test]
+    fn test_len_bookkeeping() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(1.0, 'a');
+        map.insert(2.0, 'b');
+        assert_eq!(map.remove(1.01), Some('a'));
+        assert_eq!(map.remove(1.0), None);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.map.len(), 1);
+        assert_eq!(map.remove(2.0), Some('b'));
+        assert!(map.is_empty());
+        assert_eq!(map.map.len(), 0);
+
+        map.insert(3.0, 'c');
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.iter().len(), 0);
+    }
+
+    #[test]
+    fn test_retain_drain_extract_if() {
+        let mut map: ApproxHashMap<f64, i32> = (0..20).map(|i| (i as f64, i)).collect();
+        map.retain(|_, v| *v % 2 == 0);
+        assert_eq!(map.len(), 10);
+        assert_eq!(map.get(&3.0), None);
+        assert_eq!(map.get(&4.0), Some(&4));
+
+        let mut extracted: Vec<i32> = map.extract_if(|_, v| *v % 4 == 0).map(|(_, v)| v).collect();
+        extracted.sort();
+        assert_eq!(extracted, [0, 4, 8, 12, 16]);
+        assert_eq!(map.len(), 5);
+        assert_eq!(map.map.len(), 5);
+
+        // Entries not yet visited are kept.
+        assert!(map.extract_if(|_, _| true).next().is_some());
+        assert_eq!(map.len(), 4);
+
+        let mut drained: Vec<i32> = map.drain().map(|(_, v)| v).collect();
+        drained.sort();
+        assert_eq!(drained.len(), 4);
+        assert!(map.is_empty());
+        assert_eq!(map.get(&2.0), None);
+    }
+
+    #[test]
+    fn test_get_disjoint_mut() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(1.0, 'a');
+        map.insert(2.0, 'b');
+        let [a, b, c] = map.get_disjoint_mut([1.01, 1.99, 3.0]);
+        *a.unwrap() = 'x';
+        *b.unwrap() = 'y';
+        assert_eq!(c, None);
+        assert_eq!(map[&1.0], 'x');
+        assert_eq!(map[&2.0], 'y');
+    }
+
+    #[test]
+    #[should_panic = "duplicate keys"]
+    fn test_get_disjoint_mut_duplicate() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(1.0, 'a');
+        let _ = map.get_disjoint_mut([1.0, 1.01]);
+    }
+
+    #[test]
+    fn test_get_disjoint_mut_absent() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(1.0, 'a');
+        let floats = map.float_pool().iter().count();
+        let [a, b, c] = map.get_disjoint_mut([7.0, 1.01, 7.5]);
+        assert_eq!((a, b, c), (None, Some(&mut 'a'), None));
+        assert_eq!(map.float_pool().iter().count(), floats);
+    }
+
+    #[test]
+    #[should_panic = "duplicate keys"]
+    fn test_get_disjoint_mut_absent_duplicate() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(1.0, 'a');
+        let _ = map.get_disjoint_mut([7.0, 7.01]);
+    }
+
+    #[test]
+    fn test_map_eq() {
+        let mut map1 = ApproxHashMap::with_capacity(4, Precision::absolute(3));
+        map1.extend([(1.0, 'a'), (2.0, 'b')]);
+        let mut map2 = ApproxHashMap::new(Precision::absolute(3));
+        map2.extend([(2.01, 'b'), (0.99, 'a')]);
+        assert_eq!(map1, map2);
+        map2.insert(3.0, 'c');
+        assert_ne!(map1, map2);
+        map1.insert(3.0, 'd');
+        assert_ne!(map1, map2);
+
+        // Two keys in `self` matching the same key in `other`
+        let a = ApproxHashMap::from_iter(Precision::absolute(3), [(1.0, 'x'), (1.25, 'x')]);
+        let b = ApproxHashMap::from_iter(Precision::absolute(3), [(1.125, 'x'), (5.0, 'x')]);
+        assert_eq!(b.get(&1.0), Some(&'x'));
+        assert_eq!(b.get(&1.25), Some(&'x'));
+        assert_ne!(a, b);
+        assert_ne!(b, a);
+    }
+
+    #[test]
+    fn test_map_approx_eq() {
+        let mut golden = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        golden.extend([(1.0, 10.0), (2.0, 20.0)]);
+        let mut computed = ApproxHashMap::new(Precision::absolute(2));
+        computed.extend([(2.02, 20.01), (0.98, 9.99)]);
+
+        let prec = Precision::absolute(4);
+        assert!(golden.approx_eq(&computed, prec));
+        assert!(!golden.approx_eq(&computed, Precision::absolute(10)));
+        computed.insert(3.0, 30.0);
+        assert!(!golden.approx_eq(&computed, prec));
+
+        // Two keys in `other` matching the same key in `self`
+        let mut a = ApproxHashMap::new(Precision::absolute(1)); // bucket size = 0.5
+        a.extend([(1.0, 0.0), (5.0, 0.0)]);
+        let mut b = ApproxHashMap::new(Precision::absolute(3));
+        b.extend([(1.0, 0.0), (1.2, 0.0)]);
+        assert_eq!(a.get(&1.2), Some(&0.0));
+        assert!(!a.approx_eq(&b, prec));
+    }
+
+    #[test]
+    fn test_get_within() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        for i in 0..8 {
+            map.insert(i as f64 * 0.5, i);
+        }
+
+        let near = |x, radius| {
+            let mut ret: Vec<i32> = map.get_within(x, radius).map(|(_, &v)| v).collect();
+            ret.sort();
+            ret
+        };
+        assert_eq!(near(1.02, 0), [2]);
+        assert_eq!(near(1.13, 0), [2]);
+        assert_eq!(near(1.25, 0), Vec::<i32>::new());
+        assert_eq!(near(1.25, 1), Vec::<i32>::new());
+        assert_eq!(near(1.25, 2), [2, 3]);
+        assert_eq!(near(1.02, 3), [2]);
+        assert_eq!(near(1.02, 4), [1, 2, 3]);
+        assert_eq!(near(-3.0, 1), Vec::<i32>::new());
+
+        let mut map = ApproxHashMap::new(Precision::absolute(3));
+        map.insert([0.0, 0.0], 'a');
+        map.insert([0.5, 0.0], 'b');
+        map.insert([0.5, 0.5], 'c');
+        let mut near: Vec<char> = map.get_within([0.25, 0.25], 2).map(|(_, &v)| v).collect();
+        near.sort();
+        assert_eq!(near, ['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        assert_eq!(map.nearest([0.0, 0.0]), None);
+        map.insert([0.0, 0.0], 'a');
+        map.insert([1.0, 1.0], 'b');
+        map.insert([10.0, -10.0], 'c');
+
+        assert_eq!(map.nearest([0.01, 0.0]).map(|(_, &v)| v), Some('a'));
+        assert_eq!(map.nearest([0.7, 0.9]).map(|(_, &v)| v), Some('b'));
+        assert_eq!(map.nearest([0.4, 0.4]).map(|(_, &v)| v), Some('a'));
+        assert_eq!(map.nearest([6.0, -5.0]).map(|(_, &v)| v), Some('c'));
+
+        // The key that `get_key_value()` finds is not the nearest.
+        let mut map = ApproxHashMap::new(Precision::absolute(3));
+        map.insert(0.876, 'a');
+        map.insert(1.251, 'b');
+        assert_eq!(map.get(&1.124), Some(&'a'));
+        assert_eq!(map.nearest(1.124).map(|(_, &v)| v), Some('b'));
+    }
+
+    #[test]
+    fn test_iter_sorted_and_debug() {
+        let keys = [2.0, -1.0, 0.5, 1.3, 1.5, 10.0];
+        let map1 = ApproxHashMap::from_iter(Precision::absolute(3), keys.map(|k| (k, k)));
+        let map2 = ApproxHashMap::from_iter(
+            Precision::absolute(3),
+            keys.map(|k| (k, k)).into_iter().rev(),
+        );
+
+        let sorted: Vec<f64> = map1.iter_sorted().map(|(&k, _)| k).collect();
+        assert_eq!(sorted, [-1.0, 0.5, 1.3, 1.5, 2.0, 10.0]);
+        assert_eq!(format!("{map1:?}"), format!("{map2:?}"));
+        assert_eq!(
+            format!("{map1:?}"),
+            "{-1.0: -1.0, 0.5: 0.5, 1.3: 1.3, 1.5: 1.5, 2.0: 2.0, 10.0: 10.0}",
+        );
+    }
+
+    #[test]
+    fn test_compact_float_pool() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut map = ApproxHashMap::from_iter(prec, [([1.0, 2.0], 'a'), ([3.0, 4.0], 'b')]);
+        let mut map32 = ApproxHashMap::from_iter(prec, [(1.1_f32, 'a'), (3.1_f32, 'b')]);
+        map.remove([3.0, 4.0]);
+        map32.remove(3.1);
+        map.compact_float_pool();
+        map32.compact_float_pool();
+
+        let mut floats: Vec<f64> = map.float_pool().iter().collect();
+        floats.sort_by(f64::total_cmp);
+        assert_eq!(floats, [0.0, 1.0, 2.0]);
+        assert_eq!(map.get(&[1.01, 1.99]), Some(&'a'));
+        assert_eq!(map.get(&[3.0, 4.0]), None);
+        assert_eq!(map32.float_pool().iter().count(), 2);
+        assert_eq!(map32.get(&1.11), Some(&'a'));
+    }
+
+    #[test]
+    fn test_compact_float_pool_shared_bucket() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(1.25, 'a');
+        map.insert(1.0, 'b'); // shares a neighboring bucket with 1.25
+        map.remove(1.0);
+        map.compact_float_pool();
+        map.insert(1.13, 'c');
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get_key_value(&1.25), Some((&1.25, &'c')));
+        assert_eq!(map.get_key_value(&1.13), Some((&1.25, &'c')));
     }
 }
@@ -0,0 +1,971 @@
+//! Insertion-ordered hash map that works for [`ApproxHash`]able values.
+
+use alloc::vec::{self, Vec};
+use core::fmt;
+use core::hash::{BuildHasher, Hasher};
+use core::ops::Index;
+use core::slice;
+
+use hashbrown::HashTable;
+
+use crate::hash_map::DefaultHashBuilder;
+use crate::{ApproxBorrow, ApproxHash, ApproxLookup, FloatPool, Precision};
+
+/// Approximate hash map for objects with floating-point values, which keeps
+/// entries in insertion order.
+///
+/// Keys are interned using a [`FloatPool`] exactly like in
+/// [`ApproxHashMap`](crate::ApproxHashMap), but iteration order does not
+/// depend on the hasher. Entries are stored contiguously, so they can also be
+/// accessed by index.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxIndexMap, Precision};
+///
+/// let mut vertices = ApproxIndexMap::new(Precision::DEFAULT);
+/// vertices.insert([1.0, 0.0], "a");
+/// vertices.insert([0.0, 1.0], "b");
+/// vertices.insert([1.0, 0.1 + 0.2 - 0.3], "c");
+///
+/// assert_eq!(vertices.len(), 2);
+/// assert_eq!(vertices.get_index_of(&[0.0, 1.0]), Some(1));
+/// assert_eq!(vertices.get_index(0), Some((&[1.0, 0.0], &"c")));
+/// ```
+#[derive(Clone)]
+pub struct ApproxIndexMap<K, V, S = DefaultHashBuilder> {
+    hash_builder: S,
+    pool: FloatPool,
+    core: IndexMapCore<K, V>,
+}
+
+impl<K, V, S> fmt::Debug for ApproxIndexMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S: Default> Default for ApproxIndexMap<K, V, S> {
+    /// Constructs an empty map using [`Precision::default()`].
+    fn default() -> Self {
+        Self::with_hasher(S::default(), Precision::default())
+    }
+}
+
+impl<K, V> ApproxIndexMap<K, V, DefaultHashBuilder> {
+    /// Constructs an empty map.
+    pub fn new(prec: Precision) -> ApproxIndexMap<K, V, DefaultHashBuilder> {
+        Self::with_hasher(DefaultHashBuilder::default(), prec)
+    }
+    /// Constructs an empty map with space for at least `capacity` entries.
+    pub fn with_capacity(capacity: usize, prec: Precision) -> ApproxIndexMap<K, V> {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default(), prec)
+    }
+}
+
+impl<K, V, S> ApproxIndexMap<K, V, S> {
+    /// Constructs an empty map which will use the given hash builder to hash
+    /// keys.
+    pub fn with_hasher(hash_builder: S, prec: Precision) -> ApproxIndexMap<K, V, S> {
+        Self::with_capacity_and_hasher(0, hash_builder, prec)
+    }
+    /// Constructs an empty map with space for at least `capacity` entries,
+    /// which will use the given hash builder to hash keys.
+    pub fn with_capacity_and_hasher(
+        capacity: usize,
+        hash_builder: S,
+        prec: Precision,
+    ) -> ApproxIndexMap<K, V, S> {
+        ApproxIndexMap {
+            hash_builder,
+            pool: FloatPool::new(prec),
+            core: IndexMapCore {
+                entries: Vec::with_capacity(capacity),
+                indices: HashTable::with_capacity(capacity),
+            },
+        }
+    }
+
+    /// Returns an iterator of all the entries in the map, in order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.core.entries.iter().map(|b| (&b.key, &b.value)),
+        }
+    }
+    /// Returns an iterator of mutable references to all the entries in the
+    /// map, in order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.core.entries.iter_mut().map(|b| (&b.key, &mut b.value)),
+        }
+    }
+
+    /// Returns an iterator of all the keys in the map, in order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.core.entries.iter().map(|b| &b.key),
+        }
+    }
+    /// Returns an iterator of all the keys in the map, in order, consuming the
+    /// map.
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys {
+            inner: self.core.entries.into_iter().map(|b| b.key),
+        }
+    }
+
+    /// Returns an iterator of all the values in the map, in order.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            inner: self.core.entries.iter().map(|b| &b.value),
+        }
+    }
+    /// Returns an iterator of mutable references to all the values in the
+    /// map, in order.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.core.entries.iter_mut().map(|b| &mut b.value),
+        }
+    }
+    /// Returns an iterator of all the values in the map, in order, consuming
+    /// the map.
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues {
+            inner: self.core.entries.into_iter().map(|b| b.value),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.core.entries.len()
+    }
+
+    /// Returns whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.core.entries.is_empty()
+    }
+
+    /// Returns the number of entries the map can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        usize::min(self.core.entries.capacity(), self.core.indices.capacity())
+    }
+    /// Reserves capacity for at least `additional` more entries.
+    pub fn reserve(&mut self, additional: usize) {
+        self.core.reserve(additional);
+    }
+    /// Shrinks the capacity of the map as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.core.shrink_to_fit();
+    }
+
+    /// Clears the map, removing all entries. Keeps the interned floats.
+    pub fn clear(&mut self) {
+        self.core.entries.clear();
+        self.core.indices.clear();
+    }
+
+    /// Retains only the entries for which `f` returns `true`, preserving the
+    /// order of the remaining entries.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let old_len = self.len();
+        self.core.entries.retain_mut(|b| f(&b.key, &mut b.value));
+        if self.len() != old_len {
+            self.core.rebuild_indices();
+        }
+    }
+
+    /// Returns the key-value pair at `index`, or `None` if `index` is out of
+    /// bounds.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        let b = self.core.entries.get(index)?;
+        Some((&b.key, &b.value))
+    }
+    /// Returns the key and a mutable reference to the value at `index`, or
+    /// `None` if `index` is out of bounds.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        let b = self.core.entries.get_mut(index)?;
+        Some((&b.key, &mut b.value))
+    }
+    /// Returns the first key-value pair in the map.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+    /// Returns the last key-value pair in the map.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.get_index(self.len().checked_sub(1)?)
+    }
+
+    /// Removes the entry at `index` by swapping it with the last entry, and
+    /// returns the key-value pair, or `None` if `index` is out of bounds.
+    ///
+    /// This perturbs the order of the map, but only takes constant time.
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        self.core.swap_remove_index(index)
+    }
+    /// Removes the entry at `index` by shifting all following entries down,
+    /// and returns the key-value pair, or `None` if `index` is out of bounds.
+    ///
+    /// This preserves the order of the map, but takes linear time.
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        self.core.shift_remove_index(index)
+    }
+    /// Removes the last entry of the map and returns the key-value pair, or
+    /// `None` if the map is empty.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        self.core.swap_remove_index(self.len().checked_sub(1)?)
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Returns a reference to the map's [`FloatPool`].
+    pub fn float_pool(&self) -> &FloatPool {
+        &self.pool
+    }
+
+    /// Returns the precision used to compare floats.
+    pub fn prec(&self) -> Precision {
+        self.pool.prec()
+    }
+}
+
+impl<K, V> ApproxIndexMap<K, V, DefaultHashBuilder>
+where
+    K: ApproxHash,
+{
+    /// Constructs an `ApproxIndexMap<K, V>` from an iterator of key-value
+    /// pairs.
+    ///
+    /// If the iterator produces any pairs with approximately equal keys, the
+    /// entry keeps the position of the first one and the value of the last
+    /// one.
+    pub fn from_iter<T: IntoIterator<Item = (K, V)>>(
+        prec: Precision,
+        iter: T,
+    ) -> ApproxIndexMap<K, V, DefaultHashBuilder> {
+        let mut map = Self::new(prec);
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> ApproxIndexMap<K, V, S>
+where
+    K: ApproxHash,
+{
+    /// Replaces all floats in `key` with interned ones that are approximately
+    /// equal, returning a mutated copy of `key`.
+    ///
+    /// If any floats in `key` are have not already been interned, they are
+    /// added to the pool and unmodified.
+    #[must_use = "intern() returns a mutated copy"]
+    pub fn intern(&mut self, key: K) -> K {
+        self.pool.intern(key)
+    }
+
+    /// Replaces all floats in `key` with interned ones that are approximately
+    /// equal, returning a mutated copy of `key`. Returns `None` if any floats
+    /// in `key` are not already in the pool.
+    #[must_use = "try_intern() returns a mutated copy"]
+    pub fn try_intern(&self, key: K) -> Option<K> {
+        self.pool.try_intern(key)
+    }
+}
+
+impl<K, V, S> ApproxIndexMap<K, V, S>
+where
+    K: ApproxHash,
+    S: BuildHasher,
+{
+    /// Returns an entry in the map for in-place manipulation.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let key = self.pool.intern(key);
+        let hash = self.hash_interned(&key);
+        match self.core.index_of(hash, &key) {
+            Some(index) => Entry::Occupied(OccupiedEntry {
+                core: &mut self.core,
+                index,
+            }),
+            None => Entry::Vacant(VacantEntry {
+                core: &mut self.core,
+                hash,
+                key,
+            }),
+        }
+    }
+    /// Returns the value in the map associated to the given key (or something
+    /// approximately equal).
+    ///
+    /// The key may be any borrowed form of the map's key type, such as `[f64]`
+    /// for a map with `Vec<f64>` keys.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: ApproxBorrow<Q>,
+        Q: ?Sized + ApproxLookup,
+    {
+        Some(self.get_full(key)?.2)
+    }
+    /// Returns the existing key-value pair that corresponds to the given key,
+    /// or `None` if it is not present.
+    ///
+    /// The key may be any borrowed form of the map's key type, such as `[f64]`
+    /// for a map with `Vec<f64>` keys.
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: ApproxBorrow<Q>,
+        Q: ?Sized + ApproxLookup,
+    {
+        let (_, k, v) = self.get_full(key)?;
+        Some((k, v))
+    }
+    /// Returns the index and the existing key-value pair that corresponds to
+    /// the given key, or `None` if it is not present.
+    ///
+    /// The key may be any borrowed form of the map's key type, such as `[f64]`
+    /// for a map with `Vec<f64>` keys.
+    pub fn get_full<Q>(&self, key: &Q) -> Option<(usize, &K, &V)>
+    where
+        K: ApproxBorrow<Q>,
+        Q: ?Sized + ApproxLookup,
+    {
+        let index = self.get_index_of(key)?;
+        let (k, v) = self.get_index(index)?;
+        Some((index, k, v))
+    }
+    /// Returns the index of the entry that corresponds to the given key, or
+    /// `None` if it is not present.
+    ///
+    /// The key may be any borrowed form of the map's key type, such as `[f64]`
+    /// for a map with `Vec<f64>` keys.
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: ApproxBorrow<Q>,
+        Q: ?Sized + ApproxLookup,
+    {
+        // Early exit optimization; don't bother hashing
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut f = |x| self.pool.get(x);
+        let mut h = self.hash_builder.build_hasher();
+        key.lookup_hash(&mut f, &mut h)?;
+        let hash = h.finish();
+        let entries = &self.core.entries;
+        let eq =
+            |&i: &usize| entries[i].hash == hash && key.lookup_eq(entries[i].key.borrow(), &mut f);
+        self.core.indices.find(hash, eq).copied()
+    }
+    /// Returns whether the map contains a key.
+    ///
+    /// The key may be any borrowed form of the map's key type, such as `[f64]`
+    /// for a map with `Vec<f64>` keys.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: ApproxBorrow<Q>,
+        Q: ?Sized + ApproxLookup,
+    {
+        self.get_index_of(key).is_some()
+    }
+    /// Returns a reference to the value corresponding to a key, or `None` if
+    /// it is not present.
+    ///
+    /// Unlike [`ApproxIndexMap::get()`], this takes the key by value and does
+    /// not require `K: ApproxLookup`, so it works for any [`ApproxHash`] key.
+    pub fn get_owned(&self, key: K) -> Option<&V> {
+        let index = self.get_index_of_owned(key)?;
+        Some(self.get_index(index)?.1)
+    }
+    /// Returns the index of the entry that corresponds to the given key, or
+    /// `None` if it is not present.
+    ///
+    /// Unlike [`ApproxIndexMap::get_index_of()`], this takes the key by value
+    /// and does not require `K: ApproxLookup`, so it works for any
+    /// [`ApproxHash`] key.
+    pub fn get_index_of_owned(&self, key: K) -> Option<usize> {
+        // Early exit optimization; don't bother hashing
+        if self.is_empty() {
+            return None;
+        }
+
+        let key = self.pool.try_intern(key)?;
+        self.core.index_of(self.hash_interned(&key), &key)
+    }
+    /// Returns whether the map contains a key.
+    ///
+    /// Unlike [`ApproxIndexMap::contains_key()`], this takes the key by value
+    /// and does not require `K: ApproxLookup`, so it works for any
+    /// [`ApproxHash`] key.
+    pub fn contains_key_owned(&self, key: K) -> bool {
+        self.get_index_of_owned(key).is_some()
+    }
+    /// Returns a mutable reference to the value corresponding to a key.
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
+        let index = self.get_index_of_owned(key)?;
+        Some(self.get_index_mut(index)?.1)
+    }
+    /// Inserts an entry into the map and returns the old value, if any.
+    ///
+    /// If the map already contains an approximately equal key, the entry keeps
+    /// its position. Otherwise the new entry is added at the end.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+    /// Inserts an entry into the map and returns its index and the old value,
+    /// if any.
+    ///
+    /// If the map already contains an approximately equal key, the entry keeps
+    /// its position. Otherwise the new entry is added at the end.
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        match self.entry(key) {
+            Entry::Occupied(mut e) => (e.index(), Some(e.insert(value))),
+            Entry::Vacant(e) => {
+                let index = e.index();
+                e.insert(value);
+                (index, None)
+            }
+        }
+    }
+    /// Removes an entry from the map by swapping it with the last entry, and
+    /// returns the value, or `None` if the key was not present.
+    ///
+    /// This perturbs the order of the map, but only takes constant time.
+    pub fn swap_remove(&mut self, key: K) -> Option<V> {
+        Some(self.swap_remove_entry(key)?.1)
+    }
+    /// Removes an entry from the map by swapping it with the last entry, and
+    /// returns the key-value pair, or `None` if the key was not present.
+    ///
+    /// This perturbs the order of the map, but only takes constant time.
+    pub fn swap_remove_entry(&mut self, key: K) -> Option<(K, V)> {
+        let index = self.get_index_of_owned(key)?;
+        self.core.swap_remove_index(index)
+    }
+    /// Removes an entry from the map by shifting all following entries down,
+    /// and returns the value, or `None` if the key was not present.
+    ///
+    /// This preserves the order of the map, but takes linear time.
+    pub fn shift_remove(&mut self, key: K) -> Option<V> {
+        Some(self.shift_remove_entry(key)?.1)
+    }
+    /// Removes an entry from the map by shifting all following entries down,
+    /// and returns the key-value pair, or `None` if the key was not present.
+    ///
+    /// This preserves the order of the map, but takes linear time.
+    pub fn shift_remove_entry(&mut self, key: K) -> Option<(K, V)> {
+        let index = self.get_index_of_owned(key)?;
+        self.core.shift_remove_index(index)
+    }
+
+    fn hash_interned(&self, key: &K) -> u64 {
+        let mut h = self.hash_builder.build_hasher();
+        key.interned_hash(&mut h);
+        h.finish()
+    }
+}
+
+impl<K, Q, V, S> Index<&Q> for ApproxIndexMap<K, V, S>
+where
+    K: ApproxBorrow<Q>,
+    Q: ?Sized + ApproxLookup,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not present in the map.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in ApproxIndexMap")
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for ApproxIndexMap<K, V, S>
+where
+    K: ApproxHash,
+    S: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for ApproxIndexMap<K, V, S>
+where
+    K: ApproxHash,
+    S: BuildHasher + Default,
+{
+    /// Constructs a map using [`Precision::default()`] from an iterator of
+    /// key-value pairs.
+    ///
+    /// If the iterator produces any pairs with approximately equal keys, the
+    /// entry keeps the position of the first one and the value of the last
+    /// one.
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> IntoIterator for ApproxIndexMap<K, V, S> {
+    type Item = (K, V);
+
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.core.entries.into_iter().map(|b| (b.key, b.value)),
+        }
+    }
+}
+impl<'a, K, V, S> IntoIterator for &'a ApproxIndexMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, K, V, S> IntoIterator for &'a mut ApproxIndexMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+iterator_structs! {
+    /// An owning iterator over the entries of an `ApproxIndexMap`.
+    pub struct IntoIter<K, V>(vec::IntoIter<Bucket<K, V>>) -> (K, V);
+
+    /// An owning iterator over the keys of an `ApproxIndexMap`.
+    pub struct IntoKeys<K, V>(vec::IntoIter<Bucket<K, V>>) -> K;
+
+    /// An owning iterator over the values of an `ApproxIndexMap`.
+    pub struct IntoValues<K, V>(vec::IntoIter<Bucket<K, V>>) -> V;
+
+    /// An iterator over the entries of an `ApproxIndexMap`.
+    pub struct Iter<'a, K, V>(slice::Iter<'a, Bucket<K, V>>) -> (&'a K, &'a V);
+
+    /// A mutable iterator over the entries of an `ApproxIndexMap`.
+    pub struct IterMut<'a, K, V>(slice::IterMut<'a, Bucket<K, V>>) -> (&'a K, &'a mut V);
+
+    /// An iterator over the keys of an `ApproxIndexMap`.
+    pub struct Keys<'a, K, V>(slice::Iter<'a, Bucket<K, V>>) -> &'a K;
+
+    /// An iterator over the values of an `ApproxIndexMap`.
+    pub struct Values<'a, K, V>(slice::Iter<'a, Bucket<K, V>>) -> &'a V;
+
+    /// A mutable iterator over the values of an `ApproxIndexMap`.
+    pub struct ValuesMut<'a, K, V>(slice::IterMut<'a, Bucket<K, V>>) -> &'a mut V;
+}
+impl_exact_size_for_iterator_structs!(
+    IntoIter,
+    IntoKeys,
+    IntoValues,
+    Iter<'_>,
+    IterMut<'_>,
+    Keys<'_>,
+    Values<'_>,
+    ValuesMut<'_>,
+);
+impl_clone_for_iterator_structs!(Iter, Keys, Values);
+
+/// A view into a single entry in a map, which may either be vacant or occupied.
+///
+/// This `enum` is constructed from the [`entry`] method on [`ApproxIndexMap`].
+///
+/// [`entry`]: ApproxIndexMap::entry
+pub enum Entry<'a, K, V> {
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// A vacant entry.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default if empty, and
+    /// returns a mutable reference to the value in the entry.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the default
+    /// function if empty, and returns a mutable reference to the value in the
+    /// entry.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting, if empty, the result of
+    /// the default function. This method allows for generating key-derived
+    /// values for insertion by providing the default function a reference to
+    /// the key that was moved during the `.entry(key)` method call.
+    ///
+    /// The reference to the moved key is provided so that cloning or copying
+    /// the key is unnecessary, unlike with `.or_insert_with(|| ... )`.
+    pub fn or_insert_with_key<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce(&K) -> V,
+    {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let value = default(&e.key);
+                e.insert(value)
+            }
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => &e.key,
+        }
+    }
+
+    /// Returns the index where the entry exists or would be inserted.
+    pub fn index(&self) -> usize {
+        match self {
+            Entry::Occupied(e) => e.index(),
+            Entry::Vacant(e) => e.index(),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any
+    /// potential inserts into the map.
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            Entry::Occupied(mut e) => {
+                f(e.get_mut());
+                Entry::Occupied(e)
+            }
+            Entry::Vacant(e) => Entry::Vacant(e),
+        }
+    }
+}
+
+impl<'a, K, V: Default> Entry<'a, K, V> {
+    /// Ensures a value is in the entry by inserting the default value if empty,
+    /// and returns a mutable reference to the value in the entry.
+    pub fn or_default(self) -> &'a mut V {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(Default::default()),
+        }
+    }
+}
+
+/// A view into an occupied entry in an `ApproxIndexMap`. It is part of the
+/// [`Entry`] enum.
+pub struct OccupiedEntry<'a, K, V> {
+    core: &'a mut IndexMapCore<K, V>,
+    index: usize,
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    /// Gets a reference to the key in the entry.
+    pub fn key(&self) -> &K {
+        &self.core.entries[self.index].key
+    }
+
+    /// Returns the index of the entry.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Gets a reference to the value in the entry.
+    pub fn get(&self) -> &V {
+        &self.core.entries[self.index].value
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    ///
+    /// If you need a reference to the `OccupiedEntry` which may outlive the
+    /// destruction of the `Entry` value, see [`into_mut`].
+    ///
+    /// [`into_mut`]: Self::into_mut
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.core.entries[self.index].value
+    }
+
+    /// Converts the `OccupiedEntry` into a mutable reference to the value in
+    /// the entry with a lifetime bound to the map itself.
+    ///
+    /// If you need multiple references to the `OccupiedEntry`, see [`get_mut`].
+    ///
+    /// [`get_mut`]: Self::get_mut
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.core.entries[self.index].value
+    }
+
+    /// Sets the value of the entry, and returns the entry's old value.
+    pub fn insert(&mut self, value: V) -> V {
+        core::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry by swapping it with the last entry, and returns the
+    /// key-value pair.
+    pub fn swap_remove_entry(self) -> (K, V) {
+        let ret = self.core.swap_remove_index(self.index);
+        ret.expect("index out of bounds")
+    }
+
+    /// Removes the entry by shifting all following entries down, and returns
+    /// the key-value pair.
+    pub fn shift_remove_entry(self) -> (K, V) {
+        let ret = self.core.shift_remove_index(self.index);
+        ret.expect("index out of bounds")
+    }
+
+    /// Removes the entry by swapping it with the last entry, and returns the
+    /// value.
+    pub fn swap_remove(self) -> V {
+        self.swap_remove_entry().1
+    }
+
+    /// Removes the entry by shifting all following entries down, and returns
+    /// the value.
+    pub fn shift_remove(self) -> V {
+        self.shift_remove_entry().1
+    }
+}
+
+/// A view into a vacant entry in an `ApproxIndexMap`. It is part of the
+/// [`Entry`] enum.
+pub struct VacantEntry<'a, K, V> {
+    core: &'a mut IndexMapCore<K, V>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    /// Gets a reference to the key that would be used when inserting a value
+    /// through the `VacantEntry`.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Take ownership of the key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Returns the index where the entry would be inserted, which is the end
+    /// of the map.
+    pub fn index(&self) -> usize {
+        self.core.entries.len()
+    }
+
+    /// Sets the value of the entry with the `VacantEntry`'s key, and returns a
+    /// mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let index = self.core.push(self.hash, self.key, value);
+        &mut self.core.entries[index].value
+    }
+}
+
+/// Key-value pair in an `ApproxIndexMap`, along with the hash of the key.
+#[derive(Debug, Clone)]
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+/// Entries of an `ApproxIndexMap` in order, along with a hash table of their
+/// indices.
+#[derive(Debug, Clone)]
+struct IndexMapCore<K, V> {
+    entries: Vec<Bucket<K, V>>,
+    indices: HashTable<usize>,
+}
+
+impl<K, V> IndexMapCore<K, V> {
+    fn reserve(&mut self, additional: usize) {
+        let Self { entries, indices } = self;
+        entries.reserve(additional);
+        indices.reserve(additional, |&i| entries[i].hash);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        let Self { entries, indices } = self;
+        entries.shrink_to_fit();
+        indices.shrink_to_fit(|&i| entries[i].hash);
+    }
+
+    fn push(&mut self, hash: u64, key: K, value: V) -> usize {
+        let index = self.entries.len();
+        self.entries.push(Bucket { hash, key, value });
+        let Self { entries, indices } = self;
+        indices.insert_unique(hash, index, |&i| entries[i].hash);
+        index
+    }
+
+    fn rebuild_indices(&mut self) {
+        let Self { entries, indices } = self;
+        indices.clear();
+        for (index, b) in entries.iter().enumerate() {
+            indices.insert_unique(b.hash, index, |&i| entries[i].hash);
+        }
+    }
+
+    fn remove_from_indices(&mut self, index: usize) {
+        let hash = self.entries[index].hash;
+        match self.indices.find_entry(hash, |&i| i == index) {
+            Ok(e) => drop(e.remove()),
+            Err(_) => unreachable!("missing index in ApproxIndexMap"),
+        }
+    }
+
+    fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        self.remove_from_indices(index);
+        let last = self.entries.len() - 1;
+        if index != last {
+            let last_hash = self.entries[last].hash;
+            let moved = self.indices.find_mut(last_hash, |&i| i == last);
+            *moved.expect("missing index in ApproxIndexMap") = index;
+        }
+        let b = self.entries.swap_remove(index);
+        Some((b.key, b.value))
+    }
+
+    fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        self.remove_from_indices(index);
+        for i in self.indices.iter_mut() {
+            if *i > index {
+                *i -= 1;
+            }
+        }
+        let b = self.entries.remove(index);
+        Some((b.key, b.value))
+    }
+}
+
+impl<K: ApproxHash, V> IndexMapCore<K, V> {
+    fn index_of(&self, hash: u64, key: &K) -> Option<usize> {
+        let entries = &self.entries;
+        let eq = |&i: &usize| entries[i].hash == hash && entries[i].key.interned_eq(key);
+        self.indices.find(hash, eq).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn test_index_map_order() {
+        let mut map = ApproxIndexMap::new(Precision::absolute(3)); // bucket size = 0.125
+        for (i, x) in [3.0, -1.0, 2.0, 0.5, 10.0].into_iter().enumerate() {
+            assert_eq!(map.insert_full(x, i), (i, None));
+        }
+        assert_eq!(map.insert_full(2.01, 20), (2, Some(2)));
+        assert_eq!(map.entry(9.99).index(), 4);
+        assert_eq!(map.entry(7.0).index(), 5);
+
+        assert_eq!(
+            map.keys().copied().collect::<Vec<_>>(),
+            [3.0, -1.0, 2.0, 0.5, 10.0]
+        );
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), [0, 1, 20, 3, 4]);
+        assert_eq!(map.get_index(2), Some((&2.0, &20)));
+        assert_eq!(map.get_index(5), None);
+        assert_eq!(map.get_index_of(&0.49), Some(3));
+        assert_eq!(map.get_full(&-1.01), Some((1, &-1.0, &1)));
+        assert_eq!(map.first(), Some((&3.0, &0)));
+        assert_eq!(map.last(), Some((&10.0, &4)));
+        assert_eq!(map[&10.01], 4);
+
+        map.retain(|&k, _| k > 0.0);
+        assert_eq!(
+            map.keys().copied().collect::<Vec<_>>(),
+            [3.0, 2.0, 0.5, 10.0]
+        );
+        assert_eq!(map.get_index_of(&10.0), Some(3));
+    }
+
+    #[test]
+    fn test_index_map_remove() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut map = ApproxIndexMap::from_iter(prec, (0..6).map(|i| (i as f64, i)));
+
+        assert_eq!(map.swap_remove(1.01), Some(1));
+        assert_eq!(
+            map.keys().copied().collect::<Vec<_>>(),
+            [0.0, 5.0, 2.0, 3.0, 4.0]
+        );
+        assert_eq!(map.get_index_of(&5.0), Some(1));
+
+        assert_eq!(map.shift_remove_entry(2.0), Some((2.0, 2)));
+        assert_eq!(
+            map.keys().copied().collect::<Vec<_>>(),
+            [0.0, 5.0, 3.0, 4.0]
+        );
+        for (i, &k) in map.keys().enumerate() {
+            assert_eq!(map.get_index_of(&k), Some(i));
+        }
+
+        assert_eq!(map.pop(), Some((4.0, 4)));
+        assert_eq!(map.swap_remove_index(0), Some((0.0, 0)));
+        assert_eq!(map.shift_remove_index(5), None);
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), [(3.0, 3), (5.0, 5)]);
+    }
+
+    #[test]
+    fn test_index_map_borrowed_lookup() {
+        let mut map = ApproxIndexMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(vec![0.1, -3.0], 'a');
+        map.insert(vec![0.6], 'b');
+
+        assert_eq!(map.get(&[0.12, -3.0][..]), Some(&'a'));
+        assert_eq!(map.get_index_of(&[0.58][..]), Some(1));
+        assert_eq!(map[&[0.61][..]], 'b');
+        assert!(!map.contains_key(&[0.1][..]));
+        assert_eq!(map.get_full(&vec![0.6]), Some((1, &vec![0.6], &'b')));
+
+        assert_eq!(map.get_owned(vec![0.0, -3.01]), Some(&'a'));
+        assert_eq!(map.get_index_of_owned(vec![0.62]), Some(1));
+        assert!(!map.contains_key_owned(vec![5.0]));
+    }
+}
@@ -0,0 +1,425 @@
+//! Map from half-open float intervals to values.
+
+use alloc::collections::{BTreeMap, btree_map};
+use core::fmt;
+use core::iter::FusedIterator;
+use core::ops::Range;
+
+use crate::btree_map::OrdKey;
+use crate::{FloatPool, Precision};
+
+type IntervalFn<'a, V> = fn((&'a OrdKey<f64>, &'a (f64, V))) -> (Range<f64>, &'a V);
+type IterInner<'a, V> =
+    core::iter::Map<btree_map::Iter<'a, OrdKey<f64>, (f64, V)>, IntervalFn<'a, V>>;
+type OverlappingInner<'a, V> =
+    core::iter::Map<btree_map::Range<'a, OrdKey<f64>, (f64, V)>, IntervalFn<'a, V>>;
+
+/// Approximate map from disjoint half-open intervals `start..end` to values.
+///
+/// Endpoints are interned using a [`FloatPool`], so intervals whose endpoints
+/// are approximately equal touch exactly. Touching intervals with equal values
+/// are merged, and inserting an interval overwrites any part of the existing
+/// intervals that it overlaps.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxIntervalMap, Precision};
+///
+/// let mut materials = ApproxIntervalMap::new(Precision::DEFAULT);
+/// materials.insert(0.0..1.0, "sand");
+/// materials.insert(1.0000000001..2.0, "sand");
+/// materials.insert(2.0..5.0, "clay");
+/// materials.insert(3.0..4.0, "rock");
+///
+/// assert_eq!(materials.get(1.5), Some(&"sand"));
+/// assert_eq!(materials.get_key_value(0.5), Some((0.0..2.0, &"sand")));
+/// assert_eq!(materials.get(5.0), None);
+///
+/// let layers: Vec<_> = materials.overlapping(1.5..3.5).collect();
+/// assert_eq!(layers, [(0.0..2.0, &"sand"), (2.0..3.0, &"clay"), (3.0..4.0, &"rock")]);
+/// ```
+#[derive(Clone)]
+pub struct ApproxIntervalMap<V> {
+    pool: FloatPool,
+    /// End and value of each interval, keyed by start. Intervals are nonempty
+    /// and disjoint, and all endpoints are interned.
+    map: BTreeMap<OrdKey<f64>, (f64, V)>,
+}
+
+impl<V: fmt::Debug> fmt::Debug for ApproxIntervalMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V> Default for ApproxIntervalMap<V> {
+    /// Constructs an empty map using [`Precision::default()`].
+    fn default() -> Self {
+        Self::new(Precision::default())
+    }
+}
+
+impl<V> ApproxIntervalMap<V> {
+    /// Constructs an empty map.
+    pub fn new(prec: Precision) -> Self {
+        Self {
+            pool: FloatPool::new(prec),
+            map: BTreeMap::new(),
+        }
+    }
+
+    /// Returns an iterator of all the intervals in the map, sorted by start.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            inner: self.map.iter().map(interval_of_entry),
+        }
+    }
+
+    /// Returns the number of intervals in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Clears the map, removing all intervals. Keeps the interned floats.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns a reference to the map's [`FloatPool`].
+    pub fn float_pool(&self) -> &FloatPool {
+        &self.pool
+    }
+
+    /// Returns the precision used to compare endpoints.
+    pub fn prec(&self) -> Precision {
+        self.pool.prec()
+    }
+
+    /// Returns the value of the interval containing `x`.
+    pub fn get(&self, x: f64) -> Option<&V> {
+        Some(self.get_key_value(x)?.1)
+    }
+
+    /// Returns the interval containing `x` and its value.
+    ///
+    /// `x` is contained in an interval if it is approximately equal to the
+    /// start, or between the start and end and not approximately equal to the
+    /// end.
+    pub fn get_key_value(&self, x: f64) -> Option<(Range<f64>, &V)> {
+        let x = self.existing_key(x);
+        let (start, (end, value)) = self.map.range(..=&x).next_back()?;
+        (self.prec().lt(x.key, *end)).then_some((start.key..*end, value))
+    }
+
+    /// Returns whether any interval contains `x`.
+    pub fn contains(&self, x: f64) -> bool {
+        self.get_key_value(x).is_some()
+    }
+
+    /// Returns an iterator over the intervals that overlap `range`, sorted by
+    /// start.
+    ///
+    /// Endpoints are compared approximately, so intervals that only touch
+    /// `range` do not overlap it.
+    pub fn overlapping(&self, range: Range<f64>) -> Overlapping<'_, V> {
+        let start = self.existing_key(range.start);
+        let end = self.existing_key(range.end);
+        let range = if !self.prec().lt(start.key, end.key) {
+            self.map.range(start.clone()..start)
+        } else {
+            // Include the interval that contains `start`, if any.
+            match self.map.range(..=&start).next_back() {
+                Some((k, (e, _))) if self.prec().lt(start.key, *e) => self.map.range(k..&end),
+                _ => self.map.range(start..end),
+            }
+        };
+        Overlapping {
+            inner: range.map(interval_of_entry),
+        }
+    }
+
+    /// Returns the key for `x`, using an interned float if there is one.
+    fn existing_key(&self, x: f64) -> OrdKey<f64> {
+        OrdKey {
+            key: self.pool.intern_existing(x),
+            prec: self.prec(),
+        }
+    }
+
+    /// Returns the key for an interned float.
+    fn interned_key(&self, x: f64) -> OrdKey<f64> {
+        OrdKey {
+            key: x,
+            prec: self.prec(),
+        }
+    }
+
+    /// Interns the endpoints of `range`, or returns `None` if it is empty.
+    fn intern_range(&mut self, range: Range<f64>) -> Option<(f64, f64)> {
+        if !self.prec().lt(range.start, range.end) {
+            return None;
+        }
+        let start = self.pool.intern(range.start);
+        let end = self.pool.intern(range.end);
+        (start < end).then_some((start, end))
+    }
+}
+
+impl<V: Clone> ApproxIntervalMap<V> {
+    /// Removes the part of every interval that overlaps `range`, splitting
+    /// intervals that extend past either end of it.
+    pub fn remove(&mut self, range: Range<f64>) {
+        if let Some((start, end)) = self.intern_range(range) {
+            self.remove_interned(start, end);
+        }
+    }
+
+    /// Removes the part of every interval that overlaps `start..end`, where
+    /// both endpoints are interned.
+    fn remove_interned(&mut self, start: f64, end: f64) {
+        let start_key = self.interned_key(start);
+        let end_key = self.interned_key(end);
+
+        // Truncate the interval that contains `start`, if any.
+        if let Some((_, (old_end, value))) = self.map.range_mut(..&start_key).next_back()
+            && *old_end > start
+        {
+            let old_end = core::mem::replace(old_end, start);
+            if old_end > end {
+                let value = value.clone();
+                self.map.insert(end_key, (old_end, value));
+                return;
+            }
+        }
+
+        // Remove the intervals that start inside the range, keeping the part of
+        // the last one that extends past `end`.
+        let mut inside = self.map.split_off(&start_key);
+        let mut after = inside.split_off(&end_key);
+        if let Some((_, (old_end, value))) = inside.pop_last()
+            && old_end > end
+        {
+            after.insert(end_key, (old_end, value));
+        }
+        self.map.append(&mut after);
+    }
+}
+
+impl<V: Clone + PartialEq> ApproxIntervalMap<V> {
+    /// Constructs a map from an iterator of intervals and values, which are
+    /// inserted in order.
+    pub fn from_iter<T: IntoIterator<Item = (Range<f64>, V)>>(prec: Precision, iter: T) -> Self {
+        let mut map = Self::new(prec);
+        map.extend(iter);
+        map
+    }
+
+    /// Inserts an interval into the map, overwriting the parts of any existing
+    /// intervals that it overlaps. Both endpoints are interned first.
+    ///
+    /// If an interval with an equal value touches the new one, they are
+    /// merged. Empty intervals, including ones whose endpoints are
+    /// approximately equal, are ignored.
+    pub fn insert(&mut self, range: Range<f64>, value: V) {
+        let Some((mut start, mut end)) = self.intern_range(range) else {
+            return;
+        };
+        self.remove_interned(start, end);
+
+        // Endpoints are interned, so touching intervals share an endpoint
+        // exactly.
+        let start_key = self.interned_key(start);
+        if let Some((prev_start, (prev_end, prev_value))) = self.map.range(..&start_key).next_back()
+            && *prev_end == start
+            && *prev_value == value
+        {
+            let prev_start = prev_start.clone();
+            start = prev_start.key;
+            self.map.remove(&prev_start);
+        }
+        let end_key = self.interned_key(end);
+        if let btree_map::Entry::Occupied(e) = self.map.entry(end_key)
+            && e.get().1 == value
+        {
+            (end, _) = e.remove();
+        }
+
+        self.map.insert(self.interned_key(start), (end, value));
+    }
+}
+
+impl<V: Clone + PartialEq> Extend<(Range<f64>, V)> for ApproxIntervalMap<V> {
+    fn extend<T: IntoIterator<Item = (Range<f64>, V)>>(&mut self, iter: T) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<V: Clone + PartialEq> FromIterator<(Range<f64>, V)> for ApproxIntervalMap<V> {
+    /// Constructs a map using [`Precision::default()`] from an iterator of
+    /// intervals and values, which are inserted in order.
+    fn from_iter<T: IntoIterator<Item = (Range<f64>, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, V> IntoIterator for &'a ApproxIntervalMap<V> {
+    type Item = (Range<f64>, &'a V);
+
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+fn interval_of_entry<'a, V>(
+    (start, (end, value)): (&'a OrdKey<f64>, &'a (f64, V)),
+) -> (Range<f64>, &'a V) {
+    (start.key..*end, value)
+}
+
+/// An iterator over the intervals of an `ApproxIntervalMap`.
+#[derive(Debug)]
+pub struct Iter<'a, V> {
+    inner: IterInner<'a, V>,
+}
+impl<V> Clone for Iter<'_, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<V> DoubleEndedIterator for Iter<'_, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+impl<V> ExactSizeIterator for Iter<'_, V> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl<V> FusedIterator for Iter<'_, V> {}
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Range<f64>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An iterator over the intervals of an `ApproxIntervalMap` that overlap a
+/// range.
+///
+/// This `struct` is created by [`ApproxIntervalMap::overlapping()`].
+#[derive(Debug)]
+pub struct Overlapping<'a, V> {
+    inner: OverlappingInner<'a, V>,
+}
+impl<V> Clone for Overlapping<'_, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<V> DoubleEndedIterator for Overlapping<'_, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+impl<V> FusedIterator for Overlapping<'_, V> {}
+impl<'a, V> Iterator for Overlapping<'a, V> {
+    type Item = (Range<f64>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn test_interval_map() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut map = ApproxIntervalMap::new(prec);
+        map.insert(0.0..2.0, 'a');
+        map.insert(2.01..4.0, 'b');
+        map.insert(3.99..5.0, 'b'); // merged with 2.01..4.0
+        map.insert(6.0..6.01, 'c'); // empty
+        assert_eq!(format!("{map:?}"), "{0.0..2.0: 'a', 2.0..5.0: 'b'}");
+
+        assert_eq!(map.get(-1.0), None);
+        assert_eq!(map.get(-0.01), Some(&'a'));
+        assert_eq!(map.get(1.99), Some(&'b'));
+        assert_eq!(map.get(4.5), Some(&'b'));
+        assert_eq!(map.get(4.99), None);
+        assert!(!map.contains(5.0));
+
+        let overlapping: Vec<_> = map.overlapping(1.0..2.0).collect();
+        assert_eq!(overlapping, [(0.0..2.0, &'a')]);
+        let overlapping: Vec<_> = map.overlapping(1.99..5.01).collect();
+        assert_eq!(overlapping, [(2.0..5.0, &'b')]);
+        assert_eq!(map.overlapping(3.0..2.0).count(), 0);
+        assert_eq!(map.overlapping(-3.0..-1.0).count(), 0);
+    }
+
+    #[test]
+    fn test_interval_map_overwrite() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut map = ApproxIntervalMap::from_iter(prec, [(0.0..10.0, 'a')]);
+
+        // Split one interval
+        map.insert(2.0..3.0, 'b');
+        let intervals: Vec<_> = map.iter().collect();
+        assert_eq!(
+            intervals,
+            [(0.0..2.0, &'a'), (2.0..3.0, &'b'), (3.0..10.0, &'a')]
+        );
+
+        // Cover several intervals
+        map.insert(1.0..5.0, 'c');
+        let intervals: Vec<_> = map.iter().collect();
+        assert_eq!(
+            intervals,
+            [(0.0..1.0, &'a'), (1.0..5.0, &'c'), (5.0..10.0, &'a')]
+        );
+
+        // Merge on both sides
+        map.insert(0.99..5.01, 'a');
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get_key_value(7.0), Some((0.0..10.0, &'a')));
+
+        map.remove(4.0..6.0);
+        map.remove(9.0..20.0);
+        let intervals: Vec<_> = map.iter().collect();
+        assert_eq!(intervals, [(0.0..4.0, &'a'), (6.0..9.0, &'a')]);
+        map.remove(-1.0..8.0);
+        let intervals: Vec<_> = map.iter().collect();
+        assert_eq!(intervals, [(8.0..9.0, &'a')]);
+    }
+}
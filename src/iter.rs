@@ -0,0 +1,524 @@
+//! Extension trait for iterators of approximate values.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FusedIterator;
+
+use crate::hash_map::SORT_PREC;
+use crate::{ApproxHash, ApproxHashMap, ApproxOrd, Precision};
+
+/// Extension trait for iterators of approximate values.
+pub trait ApproxIterExt: Iterator + Sized {
+    /// Returns an iterator that yields only the first of each group of
+    /// approximately equal items.
+    ///
+    /// Items are yielded lazily, and seen items are remembered using an
+    /// [`ApproxHashMap`], so whether an item is yielded is decided the same way
+    /// as whether it would be inserted as a new key. The original items are
+    /// yielded, not interned copies; use [`UniqueApprox::interned()`] to yield
+    /// interned copies instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use approx_collections::{ApproxIterExt, Precision};
+    ///
+    /// let lengths = [0.1 + 0.2, 1.0, 0.3, 2.0, 1.0];
+    /// let unique: Vec<f64> = lengths.into_iter().unique_approx(Precision::DEFAULT).collect();
+    /// assert_eq!(unique, [0.1 + 0.2, 1.0, 2.0]);
+    /// ```
+    fn unique_approx(self, prec: Precision) -> UniqueApprox<Self>
+    where
+        Self::Item: ApproxHash + Clone,
+    {
+        UniqueApprox {
+            iter: self,
+            seen: ApproxHashMap::new(prec),
+        }
+    }
+
+    /// Returns an iterator that yields only the first of each group of items
+    /// whose keys are approximately equal.
+    ///
+    /// `f` is called exactly once per item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use approx_collections::{ApproxIterExt, Precision};
+    ///
+    /// let points = [("a", [0.0, 1.0]), ("b", [1.0, 0.0]), ("c", [0.0, 0.1 + 0.2 + 0.7])];
+    /// let unique: Vec<&str> = (points.into_iter())
+    ///     .unique_approx_by_key(Precision::DEFAULT, |&(_, p)| p)
+    ///     .map(|(name, _)| name)
+    ///     .collect();
+    /// assert_eq!(unique, ["a", "b"]);
+    /// ```
+    fn unique_approx_by_key<K, F>(self, prec: Precision, f: F) -> UniqueApproxByKey<Self, K, F>
+    where
+        K: ApproxHash,
+        F: FnMut(&Self::Item) -> K,
+    {
+        UniqueApproxByKey {
+            iter: self,
+            seen: ApproxHashMap::new(prec),
+            f,
+        }
+    }
+
+    /// Returns all the items that are approximately equal to the greatest
+    /// item, in their original order.
+    ///
+    /// The greatest item is found by comparing items at the finest possible
+    /// precision, which is much closer to transitive than comparing them at
+    /// `prec`. The result may still depend on the order of the items if
+    /// several of them are within the finest precision of the greatest item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use approx_collections::{ApproxIterExt, Precision};
+    ///
+    /// let lengths = [1.0, 3.0, 2.0, 0.1 + 0.2 + 2.7, 3.0];
+    /// let longest = lengths.into_iter().approx_max_all(Precision::DEFAULT);
+    /// assert_eq!(longest, [3.0, 0.1 + 0.2 + 2.7, 3.0]);
+    /// ```
+    fn approx_max_all(self, prec: Precision) -> Vec<Self::Item>
+    where
+        Self::Item: ApproxOrd,
+    {
+        extremes(self.collect(), prec, Ordering::Greater)
+    }
+    /// Returns all the items that are approximately equal to the least item,
+    /// in their original order.
+    ///
+    /// See [`approx_max_all()`](ApproxIterExt::approx_max_all) for how the
+    /// least item is found.
+    fn approx_min_all(self, prec: Precision) -> Vec<Self::Item>
+    where
+        Self::Item: ApproxOrd,
+    {
+        extremes(self.collect(), prec, Ordering::Less)
+    }
+
+    /// Returns the index of the first item that is approximately equal to the
+    /// greatest item, or `None` if the iterator is empty.
+    ///
+    /// See [`approx_max_all()`](ApproxIterExt::approx_max_all) for how the
+    /// greatest item is found. This collects the items into a [`Vec`] first.
+    fn approx_argmax(self, prec: Precision) -> Option<usize>
+    where
+        Self::Item: ApproxOrd,
+    {
+        let items: Vec<Self::Item> = self.collect();
+        first_extreme(&items, prec, Ordering::Greater)
+    }
+    /// Returns the index of the first item that is approximately equal to the
+    /// least item, or `None` if the iterator is empty.
+    ///
+    /// See [`approx_max_all()`](ApproxIterExt::approx_max_all) for how the
+    /// least item is found. This collects the items into a [`Vec`] first.
+    fn approx_argmin(self, prec: Precision) -> Option<usize>
+    where
+        Self::Item: ApproxOrd,
+    {
+        let items: Vec<Self::Item> = self.collect();
+        first_extreme(&items, prec, Ordering::Less)
+    }
+
+    /// Returns the first items that are approximately equal to the least and
+    /// greatest items, or `None` if the iterator is empty.
+    ///
+    /// See [`approx_max_all()`](ApproxIterExt::approx_max_all) for how the
+    /// least and greatest items are found. This collects the items into a
+    /// [`Vec`] first.
+    fn approx_minmax(self, prec: Precision) -> Option<(Self::Item, Self::Item)>
+    where
+        Self::Item: ApproxOrd + Clone,
+    {
+        let items: Vec<Self::Item> = self.collect();
+        let min = first_extreme(&items, prec, Ordering::Less)?;
+        let max = first_extreme(&items, prec, Ordering::Greater)?;
+        Some((items[min].clone(), items[max].clone()))
+    }
+
+    /// Returns an iterator that clamps each item between `min` and `max` using
+    /// [`Precision::clamp()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use approx_collections::{ApproxIterExt, Precision};
+    ///
+    /// let values = [-0.5, 0.0, 1.0 + 1e-12, 1.5];
+    /// let clamped: Vec<f64> = values.into_iter().approx_clamp(Precision::DEFAULT, 0.0, 1.0).collect();
+    /// assert_eq!(clamped, [0.0, 0.0, 1.0 + 1e-12, 1.0]);
+    /// ```
+    fn approx_clamp(self, prec: Precision, min: Self::Item, max: Self::Item) -> ApproxClamp<Self>
+    where
+        Self::Item: ApproxOrd + Clone,
+    {
+        ApproxClamp {
+            iter: self,
+            prec,
+            min,
+            max,
+        }
+    }
+}
+
+impl<I: Iterator> ApproxIterExt for I {}
+
+/// An iterator that yields only the first of each group of approximately equal
+/// items.
+///
+/// This `struct` is created by the [`unique_approx`] method on
+/// [`ApproxIterExt`].
+///
+/// [`unique_approx`]: ApproxIterExt::unique_approx
+#[derive(Clone)]
+pub struct UniqueApprox<I: Iterator> {
+    iter: I,
+    seen: ApproxHashMap<I::Item, ()>,
+}
+impl<I> fmt::Debug for UniqueApprox<I>
+where
+    I: Iterator + fmt::Debug,
+    I::Item: fmt::Debug + ApproxOrd,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UniqueApprox")
+            .field("iter", &self.iter)
+            .field("seen", &self.seen)
+            .finish()
+    }
+}
+impl<I> UniqueApprox<I>
+where
+    I: Iterator,
+    I::Item: ApproxHash + Clone,
+{
+    /// Returns an iterator that yields interned copies of the items instead of
+    /// the original items.
+    ///
+    /// Each float is replaced by the first approximately equal float that was
+    /// seen, so items yielded by this iterator are never approximately equal
+    /// to each other except in their interned form.
+    pub fn interned(self) -> UniqueApproxInterned<I> {
+        UniqueApproxInterned { inner: self }
+    }
+}
+impl<I> FusedIterator for UniqueApprox<I>
+where
+    I: FusedIterator,
+    I::Item: ApproxHash + Clone,
+{
+}
+impl<I> Iterator for UniqueApprox<I>
+where
+    I: Iterator,
+    I::Item: ApproxHash + Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .find(|item| self.seen.insert(item.clone(), ()).is_none())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint(&self.iter, &self.seen)
+    }
+}
+
+/// An iterator that yields interned copies of only the first of each group of
+/// approximately equal items.
+///
+/// This `struct` is created by the [`interned`] method on [`UniqueApprox`].
+///
+/// [`interned`]: UniqueApprox::interned
+pub struct UniqueApproxInterned<I: Iterator> {
+    inner: UniqueApprox<I>,
+}
+impl<I> Clone for UniqueApproxInterned<I>
+where
+    I: Iterator + Clone,
+    I::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<I> fmt::Debug for UniqueApproxInterned<I>
+where
+    I: Iterator + fmt::Debug,
+    I::Item: fmt::Debug + ApproxOrd,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("UniqueApproxInterned")
+            .field(&self.inner)
+            .finish()
+    }
+}
+impl<I> FusedIterator for UniqueApproxInterned<I>
+where
+    I: FusedIterator,
+    I::Item: ApproxHash + Clone,
+{
+}
+impl<I> Iterator for UniqueApproxInterned<I>
+where
+    I: Iterator,
+    I::Item: ApproxHash + Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let UniqueApprox { iter, seen } = &mut self.inner;
+        for item in iter {
+            let item = seen.intern(item);
+            if seen.insert(item.clone(), ()).is_none() {
+                return Some(item);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An iterator that yields only the first of each group of items whose keys
+/// are approximately equal.
+///
+/// This `struct` is created by the [`unique_approx_by_key`] method on
+/// [`ApproxIterExt`].
+///
+/// [`unique_approx_by_key`]: ApproxIterExt::unique_approx_by_key
+#[derive(Clone)]
+pub struct UniqueApproxByKey<I, K, F> {
+    iter: I,
+    seen: ApproxHashMap<K, ()>,
+    f: F,
+}
+impl<I, K, F> fmt::Debug for UniqueApproxByKey<I, K, F>
+where
+    I: fmt::Debug,
+    K: fmt::Debug + ApproxOrd,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UniqueApproxByKey")
+            .field("iter", &self.iter)
+            .field("seen", &self.seen)
+            .finish_non_exhaustive()
+    }
+}
+impl<I, K, F> FusedIterator for UniqueApproxByKey<I, K, F>
+where
+    I: FusedIterator,
+    K: ApproxHash,
+    F: FnMut(&I::Item) -> K,
+{
+}
+impl<I, K, F> Iterator for UniqueApproxByKey<I, K, F>
+where
+    I: Iterator,
+    K: ApproxHash,
+    F: FnMut(&I::Item) -> K,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Self { iter, seen, f } = self;
+        iter.find(|item| seen.insert(f(item), ()).is_none())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        size_hint(&self.iter, &self.seen)
+    }
+}
+
+/// An iterator that clamps each item between a minimum and maximum.
+///
+/// This `struct` is created by the [`approx_clamp`] method on
+/// [`ApproxIterExt`].
+///
+/// [`approx_clamp`]: ApproxIterExt::approx_clamp
+#[derive(Debug, Clone)]
+pub struct ApproxClamp<I: Iterator> {
+    iter: I,
+    prec: Precision,
+    min: I::Item,
+    max: I::Item,
+}
+impl<I> DoubleEndedIterator for ApproxClamp<I>
+where
+    I: DoubleEndedIterator,
+    I::Item: ApproxOrd + Clone,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let x = self.iter.next_back()?;
+        Some(self.prec.clamp(x, self.min.clone(), self.max.clone()))
+    }
+}
+impl<I> ExactSizeIterator for ApproxClamp<I>
+where
+    I: ExactSizeIterator,
+    I::Item: ApproxOrd + Clone,
+{
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+impl<I> FusedIterator for ApproxClamp<I>
+where
+    I: FusedIterator,
+    I::Item: ApproxOrd + Clone,
+{
+}
+impl<I> Iterator for ApproxClamp<I>
+where
+    I: Iterator,
+    I::Item: ApproxOrd + Clone,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let x = self.iter.next()?;
+        Some(self.prec.clamp(x, self.min.clone(), self.max.clone()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Returns the index of the item that is strictly greater (if `ord` is
+/// [`Ordering::Greater`]) or strictly less (if `ord` is [`Ordering::Less`])
+/// than all others at the finest possible precision, or the first such item
+/// if there is a tie.
+fn exact_extreme<T: ApproxOrd>(items: &[T], ord: Ordering) -> Option<usize> {
+    (0..items.len()).reduce(|best, i| {
+        if items[i].approx_cmp(&items[best], SORT_PREC) == ord {
+            i
+        } else {
+            best
+        }
+    })
+}
+
+/// Returns the index of the first item that is approximately equal to the
+/// extreme item found by [`exact_extreme()`].
+fn first_extreme<T: ApproxOrd>(items: &[T], prec: Precision, ord: Ordering) -> Option<usize> {
+    let extreme = &items[exact_extreme(items, ord)?];
+    items
+        .iter()
+        .position(|x| x.approx_cmp(extreme, prec).is_eq())
+}
+
+/// Returns all the items that are approximately equal to the extreme item
+/// found by [`exact_extreme()`].
+fn extremes<T: ApproxOrd>(items: Vec<T>, prec: Precision, ord: Ordering) -> Vec<T> {
+    let Some(extreme) = exact_extreme(&items, ord) else {
+        return items;
+    };
+    let keep: Vec<bool> = (items.iter())
+        .map(|x| x.approx_cmp(&items[extreme], prec).is_eq())
+        .collect();
+    core::iter::zip(items, keep)
+        .filter_map(|(x, keep)| keep.then_some(x))
+        .collect()
+}
+
+/// Returns the size hint for an iterator that skips items that have already
+/// been seen.
+fn size_hint<I: Iterator, K>(iter: &I, seen: &ApproxHashMap<K, ()>) -> (usize, Option<usize>) {
+    let (lo, hi) = iter.size_hint();
+    (usize::from(lo > 0 && seen.is_empty()), hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn test_unique_approx() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let values = [
+            [1.0, 2.0],
+            [1.01, 3.0],
+            [0.99, 2.01],
+            [3.0, 3.0],
+            [1.0, 2.99],
+        ];
+
+        let unique: Vec<[f64; 2]> = values.into_iter().unique_approx(prec).collect();
+        assert_eq!(unique, [[1.0, 2.0], [1.01, 3.0], [3.0, 3.0]]);
+        let unique: Vec<[f64; 2]> = values.into_iter().unique_approx(prec).interned().collect();
+        assert_eq!(unique, [[1.0, 2.0], [1.0, 3.0], [3.0, 3.0]]);
+
+        let mut iter = values.into_iter().unique_approx(prec);
+        assert_eq!(iter.size_hint(), (1, Some(5)));
+        iter.next();
+        assert_eq!(iter.size_hint(), (0, Some(4)));
+    }
+
+    #[test]
+    fn test_unique_approx_by_key() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let values = [('a', 1.0), ('b', 2.0), ('c', 1.01), ('d', 2.0), ('e', 0.0)];
+        let mut calls = 0;
+        let unique: Vec<char> = (values.into_iter())
+            .unique_approx_by_key(prec, |&(_, x)| {
+                calls += 1;
+                x
+            })
+            .map(|(c, _)| c)
+            .collect();
+        assert_eq!(unique, ['a', 'b', 'e']);
+        assert_eq!(calls, 5);
+    }
+
+    #[test]
+    fn test_approx_extremes() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let values = [1.9, 0.0, 2.0, -0.01, 1.8, 1.0, 0.1];
+        // 1.8 is approximately equal to 1.9 but not to 2.0, which is greatest.
+        assert_eq!(values.into_iter().approx_max_all(prec), [1.9, 2.0]);
+        assert_eq!(values.into_iter().approx_min_all(prec), [0.0, -0.01, 0.1]);
+        assert_eq!(values.into_iter().approx_argmax(prec), Some(0));
+        assert_eq!(values.into_iter().approx_argmin(prec), Some(1));
+        assert_eq!(values.into_iter().approx_minmax(prec), Some((0.0, 1.9)));
+
+        // The result does not depend on the order of the items.
+        let mut reversed = values;
+        reversed.reverse();
+        assert_eq!(reversed.into_iter().approx_max_all(prec), [2.0, 1.9]);
+
+        let axes = [[1.0, 0.0], [0.0, 1.0], [1.0, 1e-9], [0.0, 0.0]];
+        assert_eq!(
+            axes.into_iter().approx_max_all(prec),
+            [[1.0, 0.0], [1.0, 1e-9]]
+        );
+
+        let empty: [f64; 0] = [];
+        assert!(empty.into_iter().approx_max_all(prec).is_empty());
+        assert_eq!(empty.into_iter().approx_argmin(prec), None);
+        assert_eq!(empty.into_iter().approx_minmax(prec), None);
+    }
+
+    #[test]
+    fn test_approx_clamp() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let values = [0.5, 0.99, 1.5, 2.01, 3.0];
+        let clamped: Vec<f64> = values.into_iter().approx_clamp(prec, 1.0, 2.0).collect();
+        assert_eq!(clamped, [1.0, 0.99, 1.5, 2.01, 2.0]);
+        assert_eq!(values.into_iter().approx_clamp(prec, 1.0, 2.0).len(), 5);
+    }
+}
@@ -4,11 +4,36 @@
 //!
 //! [`FloatPool`] is used for interning floats via [`ApproxInternable`] to reduce accumulated numerical
 //! error and allow direct comparison and hashing via [`ApproxHash`].
+//! [`FloatTable`] does the same, but returns a compact [`FloatId`] for each
+//! distinct float.
 //!
 //! [`ApproxHashMap`] is used for looking up approximate values.
+//! [`ApproxIndexMap`] does the same, but keeps entries in insertion order.
+//! [`ApproxMultiMap`] associates each key with multiple values, and
+//! [`ApproxCounter`] counts how many times each value occurs.
+//! [`ApproxLruCache`] memoizes results with a bounded number of entries.
+//! [`ApproxBiMap`] maps between two kinds of values in both directions.
+//!
+//! [`ApproxBTreeMap`] and [`ApproxBTreeSet`] are used for looking up
+//! approximate values in order, such as for range queries.
+//! [`ApproxBinaryHeap`] is a priority queue that can pop approximately equal
+//! keys together. [`ApproxIntervalMap`] assigns values to ranges of floats.
+//!
+//! [`ApproxClusters`] groups values that are linked by chains of approximately
+//! equal values.
+//!
+//! [`ApproxPointIndex`] finds all the stored points near a given point.
+//!
+//! [`weld()`] and [`Welder`] merge approximately equal vertices of a mesh into
+//! an index buffer.
+//!
+//! [`ApproxSliceExt`] and [`ApproxVecExt`] sort, group, search, and deduplicate
+//! slices of approximate values, and [`ApproxIterExt`] filters iterators down
+//! to unique values and finds all the items tied for the minimum or maximum.
 //!
 //! For implementing approximate comparison on your own types, see [`ApproxEq`],
-//! [`ApproxEqZero`], and [`ApproxOrd`].
+//! [`ApproxEqZero`], and [`ApproxOrd`]. [`Exact`] wraps values that should be
+//! compared exactly, such as integer IDs.
 //!
 //! # Example
 //!
@@ -26,20 +51,140 @@
 //!
 //! # Features
 //!
+//! The `std` feature is enabled by default. Without it, this crate is
+//! `#![no_std]`. It also enables `alloc`, and makes [`ApproxHashMap`] use
+//! `std::hash::RandomState` by default.
+//!
+//! The `alloc` feature is enabled by default, and provides [`FloatPool`],
+//! [`FloatTable`], [`ApproxHashMap`], [`ApproxIndexMap`], [`ApproxMultiMap`],
+//! [`ApproxCounter`], [`ApproxLruCache`], [`ApproxBiMap`], [`ApproxBTreeMap`],
+//! [`ApproxBTreeSet`], [`ApproxBinaryHeap`], [`ApproxIntervalMap`],
+//! [`ApproxClusters`], [`ApproxPointIndex`], [`Welder`], [`ApproxSliceExt`],
+//! [`ApproxVecExt`], [`ApproxIterExt`], and the trait implementations for `Vec`
+//! and `Box`. Hash maps are provided by [`hashbrown`], so `std` is not
+//! required. Without `alloc`, only [`Precision`] and the comparison traits are
+//! available, which only require `core`.
+//!
 //! The `rustc-hash` feature is enabled by default, and uses a faster hashing
-//! algorithm for the hash map inside [`FloatPool`].
+//! algorithm for the hash maps inside [`FloatPool`] and [`FloatTable`].
 //!
 //! The `derive` feature is enabled by default, and provides derive macros for
-//! [`ApproxEq`], [`ApproxEqZero`], and [`ApproxInternable`].
+//! [`ApproxEq`], [`ApproxEqZero`], [`ApproxInternable`], [`ApproxHash`], and
+//! [`ApproxLookup`].
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for
+//! [`Precision`], [`FloatPool`], and [`ApproxHashMap`]. Floats in a
+//! [`FloatPool`] are stored as exact bit patterns, and deserializing an
+//! [`ApproxHashMap`] restores its pool before inserting keys so that keys keep
+//! exactly the same values. Every bucket of a [`FloatPool`] is stored, so any
+//! float is interned the same way before and after a roundtrip.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Most items linked from the crate docs require `alloc`.
+#![cfg_attr(not(feature = "alloc"), allow(rustdoc::broken_intra_doc_links))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
+// Declared first so that the macros are available in the other modules.
+#[cfg(feature = "alloc")]
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "alloc")]
+pub mod bi_map;
+#[cfg(feature = "alloc")]
+pub mod binary_heap;
+#[cfg(feature = "alloc")]
+pub mod btree_map;
+#[cfg(feature = "alloc")]
+pub mod btree_set;
+#[cfg(feature = "alloc")]
+pub mod clusters;
+#[cfg(feature = "alloc")]
+pub mod counter;
+#[cfg(feature = "alloc")]
+pub mod float_table;
+#[cfg(feature = "alloc")]
 pub mod hash_map;
+#[cfg(feature = "alloc")]
+pub mod index_map;
+#[cfg(feature = "alloc")]
+pub mod interval_map;
+#[cfg(feature = "alloc")]
+pub mod iter;
+#[cfg(feature = "alloc")]
+pub mod lru_cache;
+#[cfg(feature = "alloc")]
+pub mod multi_map;
+#[cfg(feature = "alloc")]
+pub mod point_index;
+#[cfg(feature = "alloc")]
 pub mod pool;
 pub mod precision;
+#[cfg(feature = "alloc")]
+pub mod slice;
+#[cfg(feature = "std")]
+pub mod snapshot;
 pub mod traits;
+#[cfg(feature = "alloc")]
+pub mod weld;
+
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+/// Runs the examples in the docs of the derive macros, which cannot run as
+/// doctests of `approx_collections_derive` because it cannot depend on this
+/// crate.
+#[cfg(all(doctest, feature = "derive", feature = "alloc"))]
+mod derive_doctests {
+    #[doc = include_str!("../derive/docs/approx_eq.md")]
+    struct ApproxEq;
+    #[doc = include_str!("../derive/docs/approx_eq_zero.md")]
+    struct ApproxEqZero;
+    #[doc = include_str!("../derive/docs/approx_internable.md")]
+    struct ApproxInternable;
+    #[doc = include_str!("../derive/docs/approx_hash.md")]
+    struct ApproxHash;
+}
 
 #[cfg(feature = "derive")]
-pub use approx_collections_derive::{ApproxEq, ApproxEqZero, ApproxInternable};
+pub use approx_collections_derive::{
+    ApproxEq, ApproxEqZero, ApproxHash, ApproxInternable, ApproxLookup,
+};
+#[cfg(feature = "alloc")]
+pub use bi_map::ApproxBiMap;
+#[cfg(feature = "alloc")]
+pub use binary_heap::ApproxBinaryHeap;
+#[cfg(feature = "alloc")]
+pub use btree_map::ApproxBTreeMap;
+#[cfg(feature = "alloc")]
+pub use btree_set::ApproxBTreeSet;
+#[cfg(feature = "alloc")]
+pub use clusters::ApproxClusters;
+#[cfg(feature = "alloc")]
+pub use counter::ApproxCounter;
+#[cfg(feature = "alloc")]
+pub use float_table::{FloatId, FloatTable};
+#[cfg(feature = "alloc")]
 pub use hash_map::ApproxHashMap;
+#[cfg(feature = "alloc")]
+pub use index_map::ApproxIndexMap;
+#[cfg(feature = "alloc")]
+pub use interval_map::ApproxIntervalMap;
+#[cfg(feature = "alloc")]
+pub use iter::ApproxIterExt;
+#[cfg(feature = "alloc")]
+pub use lru_cache::ApproxLruCache;
+#[cfg(feature = "alloc")]
+pub use multi_map::ApproxMultiMap;
+#[cfg(feature = "alloc")]
+pub use point_index::ApproxPointIndex;
+#[cfg(feature = "alloc")]
 pub use pool::FloatPool;
 pub use precision::Precision;
+#[cfg(feature = "alloc")]
+pub use slice::{ApproxSliceExt, ApproxVecExt};
 pub use traits::*;
+#[cfg(feature = "alloc")]
+pub use weld::{Welder, weld};
@@ -0,0 +1,322 @@
+//! Least-recently-used cache for [`ApproxHash`]able keys.
+
+use alloc::collections::BTreeMap;
+use core::fmt;
+use core::hash::BuildHasher;
+
+use crate::hash_map::{ApproxHashMap, DefaultHashBuilder};
+use crate::{ApproxHash, FloatPool, Precision};
+
+/// Cache with a bounded number of entries that evicts the least recently used
+/// entry when it is full, where approximately equal keys are considered equal.
+///
+/// This is useful for memoizing expensive functions of floats that are called
+/// repeatedly with numerically noisy but equal arguments.
+///
+/// Keys are interned using a [`FloatPool`], which is compacted periodically as
+/// entries are evicted so that its size stays proportional to the capacity.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxLruCache, Precision};
+///
+/// let mut calls = 0;
+/// let mut cache = ApproxLruCache::new(Precision::DEFAULT, 2);
+/// let mut rotation = |angle: f64| {
+///     *cache.get_or_insert_with(angle, || {
+///         calls += 1;
+///         [[angle.cos(), -angle.sin()], [angle.sin(), angle.cos()]]
+///     })
+/// };
+///
+/// rotation(0.3);
+/// rotation(0.1 + 0.2);
+/// rotation(1.0);
+/// rotation(2.0); // evicts 0.3
+/// rotation(0.3);
+/// assert_eq!(calls, 4);
+/// ```
+#[derive(Clone)]
+pub struct ApproxLruCache<K, V, S = DefaultHashBuilder> {
+    /// Value and last use of each key.
+    map: ApproxHashMap<K, (V, u64), S>,
+    /// Key for each last use, from least to most recent.
+    order: BTreeMap<u64, K>,
+    capacity: usize,
+    next_tick: u64,
+    /// Number of evictions since the float pool was last compacted.
+    evictions: usize,
+}
+
+impl<K, V, S> fmt::Debug for ApproxLruCache<K, V, S>
+where
+    K: fmt::Debug + ApproxHash,
+    V: fmt::Debug,
+    S: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_lru()).finish()
+    }
+}
+
+impl<K, V> ApproxLruCache<K, V, DefaultHashBuilder> {
+    /// Constructs an empty cache that holds at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(prec: Precision, capacity: usize) -> ApproxLruCache<K, V, DefaultHashBuilder> {
+        Self::with_hasher(DefaultHashBuilder::default(), prec, capacity)
+    }
+}
+
+impl<K, V, S> ApproxLruCache<K, V, S> {
+    /// Constructs an empty cache that holds at most `capacity` entries, which
+    /// will use the given hash builder to hash keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_hasher(
+        hash_builder: S,
+        prec: Precision,
+        capacity: usize,
+    ) -> ApproxLruCache<K, V, S> {
+        assert!(capacity > 0, "ApproxLruCache capacity must be nonzero");
+        ApproxLruCache {
+            map: ApproxHashMap::with_hasher(hash_builder, prec),
+            order: BTreeMap::new(),
+            capacity,
+            next_tick: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Returns the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the maximum number of entries in the cache.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Clears the cache, removing all entries. Keeps the interned floats.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order.clear();
+    }
+
+    /// Returns a reference to the cache's [`FloatPool`].
+    pub fn float_pool(&self) -> &FloatPool {
+        self.map.float_pool()
+    }
+
+    /// Returns the precision used to compare keys.
+    pub fn prec(&self) -> Precision {
+        self.map.prec()
+    }
+}
+
+impl<K, V, S> ApproxLruCache<K, V, S>
+where
+    K: ApproxHash,
+    S: BuildHasher,
+{
+    /// Returns an iterator of all the entries in the cache, from least to most
+    /// recently used.
+    fn iter_lru(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.order.values().map(|key| {
+            let hash = self.map.hash_interned(key);
+            let (key, (value, _)) = (self.map.get_key_value_interned(hash, key))
+                .expect("missing key in ApproxLruCache");
+            (key, value)
+        })
+    }
+}
+
+impl<K, V, S> ApproxLruCache<K, V, S>
+where
+    K: ApproxHash + Clone,
+    S: BuildHasher,
+{
+    /// Returns the value corresponding to a key (or something approximately
+    /// equal), or inserts the result of `f` if there is none, and marks the
+    /// entry as most recently used.
+    ///
+    /// If the cache is full, the least recently used entry is evicted first.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &V {
+        let key = self.map.intern(key);
+        let hash = self.map.hash_interned(&key);
+        if !self.touch(hash, &key) {
+            self.insert_new(hash, key.clone(), f());
+        }
+        let (_, (value, _)) =
+            (self.map.get_key_value_interned(hash, &key)).expect("missing key in ApproxLruCache");
+        value
+    }
+
+    /// Returns the value corresponding to a key (or something approximately
+    /// equal), and marks the entry as most recently used.
+    pub fn get(&mut self, key: K) -> Option<&V> {
+        let key = self.map.try_intern(key)?;
+        let hash = self.map.hash_interned(&key);
+        if !self.touch(hash, &key) {
+            return None;
+        }
+        let (_, (value, _)) = self.map.get_key_value_interned(hash, &key)?;
+        Some(value)
+    }
+
+    /// Returns the value corresponding to a key (or something approximately
+    /// equal), without marking the entry as used.
+    pub fn peek(&self, key: K) -> Option<&V> {
+        self.map
+            .get_key_value_owned(key)
+            .map(|(_, (value, _))| value)
+    }
+
+    /// Returns whether the cache contains a key (or something approximately
+    /// equal), without marking the entry as used.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.map.contains_key_owned(key)
+    }
+
+    /// Inserts a key-value pair into the cache and marks it as most recently
+    /// used. Returns the old value if the key (or something approximately
+    /// equal) was already present.
+    ///
+    /// If the key was not present and the cache is full, the least recently
+    /// used entry is evicted first.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let key = self.map.intern(key);
+        let hash = self.map.hash_interned(&key);
+        if self.touch(hash, &key) {
+            let (old_value, _) =
+                (self.map.get_mut_interned(hash, &key)).expect("missing key in ApproxLruCache");
+            Some(core::mem::replace(old_value, value))
+        } else {
+            self.insert_new(hash, key, value);
+            None
+        }
+    }
+
+    /// Removes a key (or something approximately equal) from the cache,
+    /// returning its value if it was present.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let (value, tick) = self.map.remove(key)?;
+        self.order.remove(&tick);
+        Some(value)
+    }
+
+    /// Removes the least recently used entry from the cache and returns it,
+    /// or `None` if the cache is empty.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (_, key) = self.order.pop_first()?;
+        let (key, (value, _)) =
+            (self.map.remove_entry(key)).expect("missing key in ApproxLruCache");
+        Some((key, value))
+    }
+
+    /// Marks the entry for an interned key as most recently used, and returns
+    /// whether it exists.
+    fn touch(&mut self, hash: u64, key: &K) -> bool {
+        let Some((_, tick)) = self.map.get_mut_interned(hash, key) else {
+            return false;
+        };
+        let old_tick = core::mem::replace(tick, self.next_tick);
+        let key = (self.order.remove(&old_tick)).expect("missing key in ApproxLruCache");
+        self.order.insert(self.next_tick, key);
+        self.next_tick += 1;
+        true
+    }
+
+    /// Inserts an entry for an interned key that is not in the cache, evicting
+    /// the least recently used entry if the cache is full.
+    fn insert_new(&mut self, hash: u64, key: K, value: V) {
+        if self.map.len() >= self.capacity {
+            self.pop_lru();
+            self.evictions += 1;
+        }
+
+        let tick = self.next_tick;
+        self.next_tick += 1;
+        self.order.insert(tick, key.clone());
+        self.map.entry_interned(hash, key).or_insert((value, tick));
+
+        // Compact after inserting, so that the floats in the new key are kept.
+        if self.evictions >= self.capacity {
+            self.map.compact_float_pool();
+            self.evictions = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+
+    #[test]
+    fn test_lru_cache() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut cache = ApproxLruCache::new(prec, 2);
+        assert_eq!(cache.insert(1.0, 'a'), None);
+        assert_eq!(cache.insert(2.0, 'b'), None);
+        assert_eq!(cache.get(1.01), Some(&'a'));
+        assert_eq!(cache.insert(3.0, 'c'), None); // evicts 2.0
+        assert!(!cache.contains_key(2.0));
+        assert_eq!(cache.peek(0.99), Some(&'a'));
+        assert_eq!(cache.insert(3.01, 'd'), Some('c'));
+        assert_eq!(cache.len(), 2);
+
+        assert_eq!(*cache.get_or_insert_with(1.0, || 'e'), 'a');
+        assert_eq!(*cache.get_or_insert_with(4.0, || 'f'), 'f'); // evicts 3.0
+        assert_eq!(cache.pop_lru(), Some((1.0, 'a')));
+        assert_eq!(cache.remove(4.01), Some('f'));
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(5.0), None);
+    }
+
+    #[test]
+    fn test_lru_cache_compaction() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut cache = ApproxLruCache::new(prec, 4);
+        for i in 0..100 {
+            cache.get_or_insert_with(i as f64, || i);
+            assert_eq!(cache.len(), (i + 1).min(4));
+        }
+        assert!(cache.float_pool().iter().count() <= 9);
+        for i in 96..100 {
+            assert_eq!(cache.get(i as f64 + 0.01), Some(&i));
+        }
+        assert_eq!(
+            format!("{cache:?}"),
+            "{96.0: 96, 97.0: 97, 98.0: 98, 99.0: 99}"
+        );
+    }
+
+    #[test]
+    fn test_lru_cache_compaction_shared_bucket() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut cache = ApproxLruCache::new(prec, 2);
+        cache.insert(5.0, 'x');
+        cache.insert(1.25, 'a');
+        cache.insert(1.0, 'b'); // evicts 5.0
+        cache.get(1.25);
+        cache.insert(3.0, 'y'); // evicts 1.0 and compacts the pool
+        cache.get(1.25);
+
+        assert_eq!(*cache.get_or_insert_with(1.13, || 'c'), 'a');
+        assert_eq!(cache.peek(1.25), Some(&'a'));
+        assert_eq!(format!("{cache:?}"), "{3.0: 'y', 1.25: 'a'}");
+    }
+}
@@ -0,0 +1,78 @@
+//! Macros shared by the map types in this crate.
+
+/// Defines iterator structs that wrap a double-ended iterator over entries and
+/// map each entry to the output type.
+///
+/// Use [`impl_exact_size_for_iterator_structs!`] and
+/// [`impl_clone_for_iterator_structs!`] for the structs whose inner iterator
+/// supports it.
+macro_rules! iterator_structs {
+    (
+        $(
+            $(#[$attr:meta])*
+            $visibility:vis struct $struct_name:ident<
+                $($lt:lifetime,)?
+                $K:ident,
+                $V:ident
+            >($inner_type:ty) -> $output_type:ty;
+        )*
+    ) => {
+        $(
+            $(#[$attr])*
+            #[derive(Debug)]
+            $visibility struct $struct_name<$($lt,)? $K, $V> {
+                inner: core::iter::Map<
+                    $inner_type,
+                    fn(<$inner_type as Iterator>::Item) -> $output_type,
+                >,
+            }
+            impl<$($lt,)? $K, $V> DoubleEndedIterator for $struct_name<$($lt,)? $K, $V> {
+                fn next_back(&mut self) -> Option<Self::Item> {
+                    self.inner.next_back()
+                }
+            }
+            impl<$($lt,)? $K, $V> core::iter::FusedIterator for $struct_name<$($lt,)? $K, $V> {}
+            impl<$($lt,)? $K, $V> Iterator for $struct_name<$($lt,)? $K, $V> {
+                type Item = $output_type;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    self.inner.next()
+                }
+
+                fn size_hint(&self) -> (usize, Option<usize>) {
+                    self.inner.size_hint()
+                }
+            }
+        )*
+    };
+}
+
+/// Implements `ExactSizeIterator` for iterator structs defined using
+/// [`iterator_structs!`].
+macro_rules! impl_exact_size_for_iterator_structs {
+    ($($type:ident $(<$lt:lifetime>)?),* $(,)?) => {
+        $(
+            impl<K, V> ExactSizeIterator for $type<$($lt,)? K, V> {
+                fn len(&self) -> usize {
+                    self.inner.len()
+                }
+            }
+        )*
+    };
+}
+
+/// Implements `Clone` for borrowing iterator structs defined using
+/// [`iterator_structs!`].
+macro_rules! impl_clone_for_iterator_structs {
+    ($($type:ident),* $(,)?) => {
+        $(
+            impl<K, V> Clone for $type<'_, K, V> {
+                fn clone(&self) -> Self {
+                    Self {
+                        inner: self.inner.clone(),
+                    }
+                }
+            }
+        )*
+    };
+}
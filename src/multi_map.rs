@@ -0,0 +1,445 @@
+//! Hash map from [`ApproxHash`]able keys to multiple values.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::iter::FusedIterator;
+
+use crate::hash_map::{self, ApproxHashMap, DefaultHashBuilder};
+use crate::{ApproxHash, ApproxOrd, FloatPool, Precision};
+
+/// Approximate hash map that associates each key with a list of values.
+///
+/// This is equivalent to an [`ApproxHashMap<K, Vec<V>>`](ApproxHashMap) where
+/// keys with no values are removed.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxMultiMap, Precision};
+///
+/// let mut faces_by_normal = ApproxMultiMap::new(Precision::DEFAULT);
+/// faces_by_normal.insert([0.0, 0.0, 1.0], "top");
+/// faces_by_normal.insert([0.0, 0.0, -1.0], "bottom");
+/// faces_by_normal.insert([0.0, 0.1 + 0.2 - 0.3, 1.0], "lid");
+///
+/// assert_eq!(faces_by_normal.get_all([0.0, 0.0, 1.0]), ["top", "lid"]);
+/// assert_eq!(faces_by_normal.count([1.0, 0.0, 0.0]), 0);
+/// ```
+#[derive(Clone)]
+pub struct ApproxMultiMap<K, V, S = DefaultHashBuilder> {
+    map: ApproxHashMap<K, Vec<V>, S>,
+    /// Total number of values.
+    len: usize,
+}
+
+impl<K, V, S> fmt::Debug for ApproxMultiMap<K, V, S>
+where
+    K: fmt::Debug + ApproxOrd,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.map, f)
+    }
+}
+
+impl<K, V, S: Default> Default for ApproxMultiMap<K, V, S> {
+    /// Constructs an empty map using [`Precision::default()`].
+    fn default() -> Self {
+        Self::with_hasher(S::default(), Precision::default())
+    }
+}
+
+impl<K, V> ApproxMultiMap<K, V, DefaultHashBuilder> {
+    /// Constructs an empty map.
+    pub fn new(prec: Precision) -> ApproxMultiMap<K, V, DefaultHashBuilder> {
+        Self::with_hasher(DefaultHashBuilder::default(), prec)
+    }
+}
+
+impl<K, V, S> ApproxMultiMap<K, V, S> {
+    /// Constructs an empty map which will use the given hash builder to hash
+    /// keys.
+    pub fn with_hasher(hash_builder: S, prec: Precision) -> ApproxMultiMap<K, V, S> {
+        ApproxMultiMap {
+            map: ApproxHashMap::with_hasher(hash_builder, prec),
+            len: 0,
+        }
+    }
+
+    /// Returns an iterator of all the keys in the map, along with all the
+    /// values for each key in the order they were inserted.
+    pub fn groups(&self) -> Groups<'_, K, V> {
+        Groups {
+            inner: self.map.iter(),
+        }
+    }
+    /// Returns an iterator of all the key-value pairs in the map.
+    ///
+    /// Values for the same key are yielded consecutively in the order they
+    /// were inserted.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            len: self.len,
+            groups: self.map.iter(),
+            current: None,
+        }
+    }
+
+    /// Returns an iterator of all the keys in the map.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.map.keys(),
+        }
+    }
+
+    /// Returns the number of distinct keys in the map.
+    pub fn key_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns the total number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the map, removing all keys and values. Keeps the interned floats.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.len = 0;
+    }
+
+    /// Returns a reference to the map's [`BuildHasher`].
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    /// Returns a reference to the map's [`FloatPool`].
+    pub fn float_pool(&self) -> &FloatPool {
+        self.map.float_pool()
+    }
+
+    /// Returns the precision used to compare floats.
+    pub fn prec(&self) -> Precision {
+        self.map.prec()
+    }
+}
+
+impl<K, V> ApproxMultiMap<K, V, DefaultHashBuilder>
+where
+    K: ApproxHash,
+{
+    /// Constructs an `ApproxMultiMap<K, V>` from an iterator of key-value
+    /// pairs.
+    ///
+    /// Values with approximately equal keys are grouped together.
+    pub fn from_iter<T: IntoIterator<Item = (K, V)>>(
+        prec: Precision,
+        iter: T,
+    ) -> ApproxMultiMap<K, V, DefaultHashBuilder> {
+        let mut map = Self::new(prec);
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> ApproxMultiMap<K, V, S>
+where
+    K: ApproxHash,
+    S: BuildHasher,
+{
+    /// Appends a value to the list of values for a key.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.entry(key).or_default().push(value);
+        self.len += 1;
+    }
+    /// Returns all the values for a key (or something approximately equal), in
+    /// the order they were inserted. Returns an empty slice if the key is not
+    /// present.
+    pub fn get_all(&self, key: K) -> &[V] {
+        self.map.get_owned(key).map_or(&[], |vs| vs)
+    }
+    /// Returns the existing key and all its values, or `None` if the key is
+    /// not present.
+    pub fn get_key_values(&self, key: K) -> Option<(&K, &[V])> {
+        let (k, vs) = self.map.get_key_value_owned(key)?;
+        Some((k, vs))
+    }
+    /// Returns the number of values for a key.
+    pub fn count(&self, key: K) -> usize {
+        self.get_all(key).len()
+    }
+    /// Returns whether the map contains a key.
+    pub fn contains_key(&self, key: K) -> bool {
+        self.map.contains_key_owned(key)
+    }
+    /// Removes the most recently inserted value for a key and returns it, or
+    /// `None` if the key is not present.
+    ///
+    /// If that was the only value for the key, the key is removed as well.
+    pub fn remove_one(&mut self, key: K) -> Option<V> {
+        let hash_map::Entry::Occupied(mut e) = self.map.entry(key) else {
+            return None;
+        };
+        let value = e.get_mut().pop();
+        if e.get().is_empty() {
+            e.remove();
+        }
+        self.len -= value.is_some() as usize;
+        value
+    }
+    /// Removes a key and returns all its values, in the order they were
+    /// inserted. Returns an empty vector if the key is not present.
+    pub fn remove_all(&mut self, key: K) -> Vec<V> {
+        let values = self.map.remove(key).unwrap_or_default();
+        self.len -= values.len();
+        values
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for ApproxMultiMap<K, V, S>
+where
+    K: ApproxHash,
+    S: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for ApproxMultiMap<K, V, S>
+where
+    K: ApproxHash,
+    S: BuildHasher + Default,
+{
+    /// Constructs a map using [`Precision::default()`] from an iterator of
+    /// key-value pairs.
+    ///
+    /// Values with approximately equal keys are grouped together.
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, S> IntoIterator for ApproxMultiMap<K, V, S> {
+    type Item = (K, Vec<V>);
+
+    type IntoIter = hash_map::IntoIter<K, Vec<V>>;
+
+    /// Converts the map into an iterator of all its keys, along with all the
+    /// values for each key in the order they were inserted.
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+impl<'a, K, V, S> IntoIterator for &'a ApproxMultiMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the keys of an `ApproxMultiMap` and their values.
+///
+/// This `struct` is created by the [`groups`] method on [`ApproxMultiMap`].
+///
+/// [`groups`]: ApproxMultiMap::groups
+#[derive(Debug)]
+pub struct Groups<'a, K, V> {
+    inner: hash_map::Iter<'a, K, Vec<V>>,
+}
+impl<K, V> Clone for Groups<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<K, V> ExactSizeIterator for Groups<'_, K, V> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl<K, V> FusedIterator for Groups<'_, K, V> {}
+impl<'a, K, V> Iterator for Groups<'a, K, V> {
+    type Item = (&'a K, &'a [V]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, vs) = self.inner.next()?;
+        Some((k, vs))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An iterator over the keys of an `ApproxMultiMap`.
+///
+/// This `struct` is created by the [`keys`] method on [`ApproxMultiMap`].
+///
+/// [`keys`]: ApproxMultiMap::keys
+#[derive(Debug)]
+pub struct Keys<'a, K, V> {
+    inner: hash_map::Keys<'a, K, Vec<V>>,
+}
+impl<K, V> Clone for Keys<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+impl<K, V> FusedIterator for Keys<'_, K, V> {}
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An iterator over the key-value pairs of an `ApproxMultiMap`.
+///
+/// This `struct` is created by the [`iter`] method on [`ApproxMultiMap`].
+///
+/// [`iter`]: ApproxMultiMap::iter
+#[derive(Debug)]
+pub struct Iter<'a, K, V> {
+    len: usize,
+    groups: hash_map::Iter<'a, K, Vec<V>>,
+    current: Option<(&'a K, core::slice::Iter<'a, V>)>,
+}
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            len: self.len,
+            groups: self.groups.clone(),
+            current: self.current.clone(),
+        }
+    }
+}
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((k, values)) = &mut self.current
+                && let Some(v) = values.next()
+            {
+                self.len -= 1;
+                return Some((k, v));
+            }
+            let (k, values) = self.groups.next()?;
+            self.current = Some((k, values.iter()));
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_multi_map() {
+        let mut map = ApproxMultiMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(1.0, 'a');
+        map.insert(2.0, 'b');
+        map.insert(1.01, 'c');
+        map.insert(0.99, 'd');
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.key_count(), 2);
+        assert_eq!(map.get_all(1.02), ['a', 'c', 'd']);
+        assert_eq!(map.get_key_values(2.01), Some((&2.0, &['b'][..])));
+        assert_eq!(map.count(3.0), 0);
+        assert_eq!(map.get_all(3.0), [] as [char; 0]);
+
+        let mut pairs: Vec<(f64, char)> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        pairs.sort_by_key(|&(_, v)| v);
+        assert_eq!(pairs, [(1.0, 'a'), (2.0, 'b'), (1.0, 'c'), (1.0, 'd')]);
+        assert_eq!(map.iter().len(), 4);
+        assert_eq!(map.groups().map(|(_, vs)| vs.len()).sum::<usize>(), 4);
+        let mut keys: Vec<f64> = map.keys().copied().collect();
+        keys.sort_by(f64::total_cmp);
+        assert_eq!(keys, [1.0, 2.0]);
+        assert_eq!(map.keys().len(), 2);
+
+        assert_eq!(map.remove_one(1.0), Some('d'));
+        assert_eq!(map.remove_one(2.0), Some('b'));
+        assert_eq!(map.remove_one(2.0), None);
+        assert!(!map.contains_key(2.0));
+        assert_eq!(map.len(), 2);
+
+        assert_eq!(map.remove_all(1.0), vec!['a', 'c']);
+        assert_eq!(map.remove_all(1.0), vec![]);
+        assert!(map.is_empty());
+        assert_eq!(map.key_count(), 0);
+    }
+
+    #[test]
+    fn test_remove_one_empties_key() {
+        let mut map = ApproxMultiMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert(1.0, 'a');
+        map.insert(1.01, 'b');
+        map.insert(2.0, 'c');
+
+        assert_eq!(map.remove_one(0.99), Some('b'));
+        assert!(map.contains_key(1.0));
+        assert_eq!(map.remove_one(0.99), Some('a'));
+        assert!(!map.contains_key(1.0));
+        assert_eq!(map.get_key_values(1.0), None);
+        assert_eq!(map.key_count(), 1);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.groups().count(), 1);
+        assert_eq!(map.remove_one(1.0), None);
+        assert_eq!(map.len(), 1);
+
+        map.insert(1.02, 'd');
+        assert_eq!(map.get_all(1.0), ['d']);
+        assert_eq!(map.key_count(), 2);
+    }
+
+    #[test]
+    fn test_collect() {
+        // These are not exactly equal, but are at the default precision.
+        assert_ne!(0.1 + 0.2, 0.3);
+        let mut map: ApproxMultiMap<f64, char> =
+            [(0.1 + 0.2, 'a'), (0.3, 'b')].into_iter().collect();
+        map.extend([(0.7 - 0.4, 'c'), (0.4, 'd')]);
+        assert_eq!(map.get_all(0.3), ['a', 'b', 'c']);
+        assert_eq!(map.key_count(), 2);
+    }
+}
@@ -0,0 +1,300 @@
+//! Spatial index for looking up nearby points.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FusedIterator;
+
+use hashbrown::{HashMap, hash_map};
+use smallvec::SmallVec;
+
+use crate::{ApproxEq, Precision};
+
+/// Points and values in a single grid cell.
+type Cell<const N: usize, V> = SmallVec<[([f64; N], V); 1]>;
+type CellIter<'a, const N: usize, V> =
+    core::iter::Flatten<hash_map::Values<'a, [u64; N], Cell<N, V>>>;
+
+/// Spatial index for `N`-dimensional points, for finding all the stored points
+/// that are approximately equal to a given point.
+///
+/// Unlike [`ApproxHashMap`](crate::ApproxHashMap), points are not interned.
+/// Instead, each point is stored in a grid cell determined by the bucket of
+/// each coordinate, and queries search the `3^N` cells around a point. Multiple
+/// points may be stored at the same location.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxPointIndex, Precision};
+///
+/// let mut vertices = ApproxPointIndex::new(Precision::DEFAULT);
+/// vertices.insert([0.0, 0.0, 1.0], 0);
+/// vertices.insert([0.0, 1.0, 0.0], 1);
+/// vertices.insert([0.0, 0.1 + 0.2 - 0.3, 1.0], 2);
+///
+/// let near: Vec<_> = vertices.query_near([0.0, 0.0, 1.0]).into_iter().map(|(_, &i)| i).collect();
+/// assert_eq!(near, [0, 2]);
+/// assert_eq!(vertices.nearest([0.0, 1.0, 0.0]), Some((&[0.0, 1.0, 0.0], &1)));
+/// ```
+#[derive(Clone)]
+pub struct ApproxPointIndex<const N: usize, V> {
+    prec: Precision,
+    cells: HashMap<[u64; N], Cell<N, V>>,
+    len: usize,
+}
+
+impl<const N: usize, V: fmt::Debug> fmt::Debug for ApproxPointIndex<N, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<const N: usize, V> Default for ApproxPointIndex<N, V> {
+    /// Constructs an empty index using [`Precision::default()`].
+    fn default() -> Self {
+        Self::new(Precision::default())
+    }
+}
+
+impl<const N: usize, V> ApproxPointIndex<N, V> {
+    /// Constructs an empty index.
+    pub fn new(prec: Precision) -> Self {
+        Self {
+            prec,
+            cells: HashMap::new(),
+            len: 0,
+        }
+    }
+
+    /// Constructs an index from an iterator of points and values.
+    pub fn from_iter<T: IntoIterator<Item = ([f64; N], V)>>(prec: Precision, iter: T) -> Self {
+        let mut index = Self::new(prec);
+        index.extend(iter);
+        index
+    }
+
+    /// Returns an iterator of all the points and values in the index, in an
+    /// undefined order.
+    pub fn iter(&self) -> Iter<'_, N, V> {
+        Iter {
+            len: self.len,
+            inner: self.cells.values().flatten(),
+        }
+    }
+
+    /// Returns the number of points in the index.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Clears the index, removing all points.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.len = 0;
+    }
+
+    /// Returns the precision used to compare coordinates.
+    pub fn prec(&self) -> Precision {
+        self.prec
+    }
+
+    /// Adds a point to the index. Existing points at the same location are not
+    /// affected.
+    pub fn insert(&mut self, point: [f64; N], value: V) {
+        let cell = point.map(|x| self.prec.bucket(x));
+        self.cells.entry(cell).or_default().push((point, value));
+        self.len += 1;
+    }
+
+    /// Returns all the points in the index that are approximately equal to
+    /// `point`, along with their values, sorted by distance from `point`.
+    pub fn query_near(&self, point: [f64; N]) -> Vec<(&[f64; N], &V)> {
+        let mut ret: Vec<(f64, (&[f64; N], &V))> = self
+            .neighbor_cells(point)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(|(p, _)| p.approx_eq(&point, self.prec))
+            .map(|(p, v)| (distance_squared(p, &point), (p, v)))
+            .collect();
+        ret.sort_by(|(d1, _), (d2, _)| d1.total_cmp(d2));
+        ret.into_iter().map(|(_, pv)| pv).collect()
+    }
+
+    /// Returns the nearest point in the index that is approximately equal to
+    /// `point`, along with its value, or `None` if there is no such point.
+    pub fn nearest(&self, point: [f64; N]) -> Option<(&[f64; N], &V)> {
+        let (cell, i) = self.nearest_position(point)?;
+        let (p, v) = &self.cells[&cell][i];
+        Some((p, v))
+    }
+
+    /// Removes the nearest point in the index that is approximately equal to
+    /// `point`, and returns it along with its value, or `None` if there is no
+    /// such point.
+    pub fn remove(&mut self, point: [f64; N]) -> Option<([f64; N], V)> {
+        let (cell, i) = self.nearest_position(point)?;
+        let hash_map::EntryRef::Occupied(mut e) = self.cells.entry_ref(&cell) else {
+            unreachable!("missing cell in ApproxPointIndex");
+        };
+        let ret = e.get_mut().remove(i);
+        if e.get().is_empty() {
+            e.remove();
+        }
+        self.len -= 1;
+        Some(ret)
+    }
+
+    /// Returns the cell and the index within that cell of the nearest point
+    /// that is approximately equal to `point`.
+    fn nearest_position(&self, point: [f64; N]) -> Option<([u64; N], usize)> {
+        self.neighbor_cells(point)
+            .filter_map(|cell| Some((cell, self.cells.get(&cell)?)))
+            .flat_map(|(cell, entries)| entries.iter().enumerate().map(move |(i, e)| (cell, i, e)))
+            .filter(|(_, _, (p, _))| p.approx_eq(&point, self.prec))
+            .min_by(|(_, _, (p1, _)), (_, _, (p2, _))| {
+                distance_squared(p1, &point).total_cmp(&distance_squared(p2, &point))
+            })
+            .map(|(cell, i, _)| (cell, i))
+    }
+
+    /// Returns an iterator over the cells that may contain points that are
+    /// approximately equal to `point`.
+    fn neighbor_cells(&self, point: [f64; N]) -> impl Iterator<Item = [u64; N]> {
+        let candidates = point.map(|x| {
+            let (lo, mid, hi) = self.prec.nearby_buckets(x);
+            [lo, Some(mid), hi]
+        });
+        (0..3_usize.pow(N as u32)).filter_map(move |mut n| {
+            let mut cell = [0; N];
+            for (bucket, c) in core::iter::zip(&mut cell, &candidates) {
+                *bucket = c[n % 3]?;
+                n /= 3;
+            }
+            Some(cell)
+        })
+    }
+}
+
+impl<const N: usize, V> Extend<([f64; N], V)> for ApproxPointIndex<N, V> {
+    fn extend<T: IntoIterator<Item = ([f64; N], V)>>(&mut self, iter: T) {
+        for (point, value) in iter {
+            self.insert(point, value);
+        }
+    }
+}
+
+impl<const N: usize, V> FromIterator<([f64; N], V)> for ApproxPointIndex<N, V> {
+    /// Constructs an index using [`Precision::default()`] from an iterator of
+    /// points and values.
+    fn from_iter<T: IntoIterator<Item = ([f64; N], V)>>(iter: T) -> Self {
+        let mut index = Self::default();
+        index.extend(iter);
+        index
+    }
+}
+
+impl<'a, const N: usize, V> IntoIterator for &'a ApproxPointIndex<N, V> {
+    type Item = (&'a [f64; N], &'a V);
+
+    type IntoIter = Iter<'a, N, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the points of an `ApproxPointIndex`.
+///
+/// This `struct` is created by the [`iter`] method on [`ApproxPointIndex`].
+///
+/// [`iter`]: ApproxPointIndex::iter
+#[derive(Debug)]
+pub struct Iter<'a, const N: usize, V> {
+    len: usize,
+    inner: CellIter<'a, N, V>,
+}
+impl<const N: usize, V> Clone for Iter<'_, N, V> {
+    fn clone(&self) -> Self {
+        Self {
+            len: self.len,
+            inner: self.inner.clone(),
+        }
+    }
+}
+impl<const N: usize, V> ExactSizeIterator for Iter<'_, N, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<const N: usize, V> FusedIterator for Iter<'_, N, V> {}
+impl<'a, const N: usize, V> Iterator for Iter<'a, N, V> {
+    type Item = (&'a [f64; N], &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (p, v) = self.inner.next()?;
+        self.len -= 1;
+        Some((p, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+/// Returns the squared Euclidean distance between two points.
+fn distance_squared<const N: usize>(a: &[f64; N], b: &[f64; N]) -> f64 {
+    core::iter::zip(a, b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_index() {
+        let mut index = ApproxPointIndex::new(Precision::absolute(3)); // bucket size = 0.125
+        index.insert([1.0, 1.0], 'a');
+        index.insert([1.1, 0.9], 'b');
+        index.insert([1.0, 1.2], 'c');
+        index.insert([1.0, 2.0], 'd');
+        index.insert([1.0, 1.0], 'e');
+        assert_eq!(index.len(), 5);
+
+        let near: Vec<char> = index
+            .query_near([1.01, 1.0])
+            .into_iter()
+            .map(|(_, &v)| v)
+            .collect();
+        assert_eq!(near.len(), 4); // 'a' and 'e' are tied
+        assert_eq!(near[2..], ['b', 'c']);
+        let near: Vec<char> = index
+            .query_near([1.0, 1.15])
+            .into_iter()
+            .map(|(_, &v)| v)
+            .collect();
+        assert_eq!(near.len(), 3);
+        assert_eq!(near[0], 'c');
+        assert_eq!(index.nearest([1.09, 0.91]), Some((&[1.1, 0.9], &'b')));
+        assert_eq!(index.nearest([3.0, 3.0]), None);
+
+        assert_eq!(index.remove([1.01, 1.99]), Some(([1.0, 2.0], 'd')));
+        assert_eq!(index.remove([1.01, 1.99]), None);
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.iter().len(), 4);
+    }
+
+    #[test]
+    fn test_point_index_neighbor_cells() {
+        // Points on either side of a cell boundary are found from both sides.
+        let mut index = ApproxPointIndex::new(Precision::absolute(3)); // bucket size = 0.125
+        index.insert([0.499, -0.499, 0.499], 0);
+        index.insert([0.501, -0.501, 0.501], 1);
+        assert_eq!(index.query_near([0.499, -0.499, 0.499]).len(), 2);
+        assert_eq!(index.query_near([0.501, -0.501, 0.501]).len(), 2);
+    }
+}
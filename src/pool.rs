@@ -1,15 +1,18 @@
 //! Interner that canonicalizes similar floats.
 
-use std::collections::hash_map;
-use std::fmt;
-use std::iter::FusedIterator;
+use alloc::collections::BTreeMap;
+use alloc::vec::{self, Vec};
+use core::fmt;
+use core::iter::FusedIterator;
+
+use hashbrown::hash_map;
 
 use crate::{ApproxInternable, Precision};
 
 #[cfg(feature = "rustc-hash")]
-type HashMap<K, V> = rustc_hash::FxHashMap<K, V>;
+pub(crate) type HashMap<K, V> = hashbrown::HashMap<K, V, rustc_hash::FxBuildHasher>;
 #[cfg(not(feature = "rustc-hash"))]
-type HashMap<K, V> = std::collections::HashMap<K, V>;
+pub(crate) type HashMap<K, V> = hashbrown::HashMap<K, V>;
 
 /// Structure for interning similar floats based on approximate equality.
 ///
@@ -37,8 +40,7 @@ pub struct FloatPool {
 
 impl fmt::Debug for FloatPool {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let floats: std::collections::BTreeMap<_, _> =
-            self.floats.iter().map(|(&k, &v)| (k, v)).collect();
+        let floats: BTreeMap<_, _> = self.floats.iter().map(|(&k, &v)| (k, v)).collect();
         f.debug_struct("FloatPool")
             .field("prec", &self.prec)
             .field("floats", &floats)
@@ -102,24 +104,36 @@ impl FloatPool {
         (!failed).then_some(value)
     }
 
+    /// Replaces floats in `value` with interned ones that are approximately
+    /// equal, returning a mutated copy of `value`. Floats that are not already
+    /// in the pool are unmodified.
+    pub(crate) fn intern_existing<V: ApproxInternable>(&self, mut value: V) -> V {
+        value.intern_floats(&mut |x| {
+            if let Some(saved) = self.get(*x) {
+                *x = saved;
+            }
+        });
+        value
+    }
+
     /// Searches for an existing hash value for a float that is approximately
     /// equal to `x`, and returns it and its bucket if found. Returns `None` if
     /// there is no existing value that is close to `x`.
-    fn get(&self, x: f64) -> Option<f64> {
+    pub(crate) fn get(&self, x: f64) -> Option<f64> {
         self.floats.get(&self.prec.bucket(x)).copied()
     }
 
     /// Searches for an existing bucket value for a float that is approximately
     /// equal to `x`, and returns the existing float and its bucket if found. If
     /// none is found, inserts it and returns itself and its bucket.
-    fn insert(&mut self, x: f64) -> (f64, u64) {
+    pub(crate) fn insert(&mut self, x: f64) -> (f64, u64) {
         let (lo, mid, hi) = self.prec.nearby_buckets(x);
         match self.floats.entry(mid) {
-            std::collections::hash_map::Entry::Occupied(e) => {
+            hash_map::Entry::Occupied(e) => {
                 let f = *e.get();
                 (f, self.prec.bucket(f))
             }
-            std::collections::hash_map::Entry::Vacant(e) => {
+            hash_map::Entry::Vacant(e) => {
                 e.insert(x);
                 if let Some(k) = lo {
                     self.floats.insert(k, x);
@@ -132,11 +146,103 @@ impl FloatPool {
         }
     }
 
+    /// Removes all the floats in the pool for which `f` returns `false`, except
+    /// that zero is always kept as in a new pool.
+    ///
+    /// Floats that are kept still occupy their own buckets. A neighboring
+    /// bucket that was shared with a removed float is given back to a float
+    /// that is kept, if any, so that a float inserted later can never take
+    /// over a bucket next to one that is kept.
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(f64) -> bool) {
+        self.floats.retain(|_, &mut x| f(x));
+        self.floats.entry(0).or_insert(0.0);
+
+        // Sort so that the result does not depend on the order of the hash map.
+        let mut kept: Vec<f64> = self.iter().collect();
+        kept.sort_by(f64::total_cmp);
+        for x in kept {
+            let (lo, mid, hi) = self.prec.nearby_buckets(x);
+            // Zero does not occupy its neighbors in a new pool.
+            if mid == 0 {
+                continue;
+            }
+            for k in [lo, hi].into_iter().flatten() {
+                self.floats.entry(k).or_insert(x);
+            }
+        }
+    }
+
+    /// Returns the distinct floats in the pool whose own bucket is at most
+    /// `radius` buckets away from the bucket containing `x`, sorted by distance
+    /// from `x`.
+    ///
+    /// The float returned by [`FloatPool::get()`] is always included, if any,
+    /// even though its own bucket may be next to the one containing `x`. With
+    /// `radius = 0`, this returns only that float.
+    pub(crate) fn get_within(&self, x: f64, radius: u32) -> Vec<f64> {
+        let (mut lo, _, mut hi) = self.prec.nearby_buckets(x);
+        let mut ret: Vec<f64> = self.get(x).into_iter().collect();
+        for _ in 0..radius {
+            for bucket in [&mut lo, &mut hi] {
+                if let Some(b) = *bucket {
+                    // Floats are also stored in the buckets next to their own.
+                    let own = self.floats.get(&b).filter(|&&f| self.prec.bucket(f) == b);
+                    ret.extend(own);
+                }
+            }
+            lo = lo.and_then(|b| self.prec.nearby_buckets(f64::from_bits(b)).0);
+            hi = hi.and_then(|b| self.prec.nearby_buckets(f64::from_bits(b)).2);
+        }
+        ret.sort_unstable_by_key(|f| f.to_bits());
+        ret.dedup_by_key(|f| f.to_bits());
+        ret.sort_by(|a, b| (a - x).abs().total_cmp(&(b - x).abs()));
+        ret
+    }
+
+    /// Returns every occupied bucket and the float in it, including the
+    /// neighboring buckets that each float occupies, sorted by bucket.
+    #[cfg(feature = "serde")]
+    pub(crate) fn buckets(&self) -> Vec<(u64, f64)> {
+        let mut buckets: Vec<(u64, f64)> = self.floats.iter().map(|(&k, &v)| (k, v)).collect();
+        buckets.sort_unstable_by_key(|&(k, _)| k);
+        buckets
+    }
+
+    /// Constructs a pool from the buckets returned by [`FloatPool::buckets()`].
+    ///
+    /// Returns `None` if any bucket contains a float that is not in that bucket
+    /// or next to it, or a float whose own bucket contains a different float.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_buckets(
+        prec: Precision,
+        buckets: impl IntoIterator<Item = (u64, f64)>,
+    ) -> Option<Self> {
+        let mut pool = Self::new(prec);
+        pool.floats.extend(buckets);
+        let valid = pool.floats.iter().all(|(&k, &v)| {
+            let (lo, mid, hi) = prec.nearby_buckets(v);
+            [lo, Some(mid), hi].contains(&Some(k))
+                && pool
+                    .floats
+                    .get(&mid)
+                    .is_some_and(|f| f.to_bits() == v.to_bits())
+        });
+        valid.then_some(pool)
+    }
+
     /// Returns the number of occupied buckets in the pool.
     pub fn bucket_count(&self) -> usize {
         self.floats.len()
     }
 
+    /// Returns a vector of all floats in the pool, sorted using
+    /// [`f64::total_cmp()`].
+    pub fn iter_sorted(&self) -> vec::IntoIter<f64> {
+        let mut floats: Vec<f64> = self.iter().collect();
+        floats.sort_by(f64::total_cmp);
+        floats.into_iter()
+    }
+
     /// Iterates over all floats in the pool, in an undefined order.
     pub fn iter(&self) -> Iter<'_> {
         Iter(FloatIterInner {
@@ -198,7 +304,7 @@ impl Iterator for IntoIter {
 impl FusedIterator for IntoIter {}
 
 type CopiedHashMapIter<'a> =
-    std::iter::Map<hash_map::Iter<'a, u64, f64>, fn((&'a u64, &'a f64)) -> (u64, f64)>;
+    core::iter::Map<hash_map::Iter<'a, u64, f64>, fn((&'a u64, &'a f64)) -> (u64, f64)>;
 
 /// Iterator over floats in a [`FloatPool`].
 #[derive(Debug)]
@@ -236,4 +342,14 @@ mod tests {
         assert_eq!([0.0, 0.0, 0.5], interner.intern([0.1, 0.0, 0.5]));
         assert_eq!([0.5, 0.8, 0.8], interner.intern([0.6, 0.8, 0.75]));
     }
+
+    #[test]
+    fn test_iter_sorted() {
+        let mut interner = FloatPool::new(Precision::absolute(3)); // bucket size = 0.125
+        for x in [2.0, -1.0, 0.5, 0.51, 10.0, -3.0] {
+            let _ = interner.intern(x);
+        }
+        let sorted: Vec<f64> = interner.iter_sorted().collect();
+        assert_eq!(sorted, [-3.0, -1.0, 0.0, 0.5, 2.0, 10.0]);
+    }
 }
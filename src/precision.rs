@@ -1,6 +1,6 @@
 //! Precision level for comparing floats.
 
-use std::cmp::Ordering;
+use core::cmp::Ordering;
 
 use crate::{ApproxCmpZero, ApproxEq, ApproxEqZero, ApproxOrd};
 
@@ -175,6 +175,28 @@ impl Precision {
         }
     }
 
+    /// Returns the value for `absolute` that reconstructs this `Precision`
+    /// when passed to [`Precision::new()`].
+    ///
+    /// Out-of-range values are clamped, so this may differ from the value
+    /// originally passed to the constructor.
+    pub const fn absolute_param(self) -> i32 {
+        MAX_ABSOLUTE + 1 - self.min_exponent as i32
+    }
+    /// Returns the value for `relative` that reconstructs this `Precision`
+    /// when passed to [`Precision::new()`].
+    pub const fn relative_param(self) -> u32 {
+        self.mantissa_bits
+    }
+
+    /// Returns whether `self` and `other` sort every float into the same
+    /// bucket.
+    #[cfg(feature = "std")]
+    pub(crate) fn has_same_buckets(self, other: Precision) -> bool {
+        self.min_exponent == other.min_exponent
+            && self.mantissa_bits.min(MAX_RELATIVE) == other.mantissa_bits.min(MAX_RELATIVE)
+    }
+
     /// Compares two floating-point numbers for equality.
     pub(crate) fn f32_eq(self, a: f32, b: f32) -> bool {
         self.f64_eq(a as f64, b as f64)
@@ -212,12 +234,12 @@ impl Precision {
     fn bucket_mask(self, f: f64) -> u64 {
         match f.classify() {
             // +INF, -INF, and each NaN gets its own bucket
-            std::num::FpCategory::Nan | std::num::FpCategory::Infinite => u64::MAX,
+            core::num::FpCategory::Nan | core::num::FpCategory::Infinite => u64::MAX,
 
             // zero and subnormal all share one bucket
-            std::num::FpCategory::Zero | std::num::FpCategory::Subnormal => 0,
+            core::num::FpCategory::Zero | core::num::FpCategory::Subnormal => 0,
 
-            std::num::FpCategory::Normal => {
+            core::num::FpCategory::Normal => {
                 let exponent = f64_exponent(f);
                 let Some(spare_mantissa_bits) = exponent.checked_sub(self.min_exponent) else {
                     return 0; // close to zero
@@ -302,6 +324,25 @@ impl Precision {
     pub fn gt_eq<T: ApproxOrd>(self, a: T, b: T) -> bool {
         !self.lt(a, b)
     }
+    /// Returns `min` if `x < min`, `max` if `x > max`, and otherwise `x`, using
+    /// `T::approx_cmp()`.
+    ///
+    /// Values that are approximately equal to `min` or `max` are returned
+    /// unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min > max`.
+    pub fn clamp<T: ApproxOrd>(self, x: T, min: T, max: T) -> T {
+        assert!(min.approx_cmp(&max, self).is_le(), "min > max");
+        if x.approx_cmp(&min, self).is_lt() {
+            min
+        } else if x.approx_cmp(&max, self).is_gt() {
+            max
+        } else {
+            x
+        }
+    }
 
     /// Returns whether `a` is approximately equal to zero.
     pub fn eq_zero<T: ApproxEqZero>(self, a: T) -> bool {
@@ -421,6 +462,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_clamp() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        assert_eq!(prec.clamp(0.5, 1.0, 2.0), 1.0);
+        assert_eq!(prec.clamp(0.99, 1.0, 2.0), 0.99);
+        assert_eq!(prec.clamp(1.5, 1.0, 2.0), 1.5);
+        assert_eq!(prec.clamp(2.01, 1.0, 2.0), 2.01);
+        assert_eq!(prec.clamp(3.0, 1.0, 2.0), 2.0);
+        assert_eq!(prec.clamp(3.0, 2.0, 1.99), 1.99);
+    }
+
     #[test]
     fn test_round_to_integer() {
         let prec = Precision::new(0, MAX_RELATIVE); // integer precision
@@ -526,6 +578,14 @@ mod tests {
         assert_eq!(prec.f64_eq(a, b), expected_eq);
     }
 
+    #[proptest_macro::property_test]
+    fn proptest_params_roundtrip(f: f64, prec: Precision) {
+        let prec2 = Precision::new(prec.absolute_param(), prec.relative_param());
+        assert_eq!(prec.bucket(f), prec2.bucket(f));
+        assert_eq!(prec.min_exponent, prec2.min_exponent);
+        assert_eq!(prec.mantissa_bits, prec2.mantissa_bits);
+    }
+
     #[proptest_macro::property_test]
     fn proptest_eq_zero(f: f64, prec: Precision) {
         assert_eq!(prec.f64_eq_zero(f), prec.f64_eq(0.0, f));
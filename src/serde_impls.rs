@@ -0,0 +1,202 @@
+//! [`serde`] support for [`Precision`], [`FloatPool`], and [`ApproxHashMap`].
+//!
+//! - [`Precision`] is stored as its `absolute` and `relative` parameters.
+//! - [`FloatPool`] is stored as its precision and every occupied bucket along
+//!   with the bit pattern of the float in it, so that no value is lost to float
+//!   formatting and every float is interned the same way after a roundtrip.
+//! - [`ApproxHashMap`] is stored as its float pool and a sequence of key-value
+//!   pairs. The pool is restored before any keys are inserted, so keys are
+//!   interned to exactly the same values they had when serialized.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::hash::BuildHasher;
+#[cfg(feature = "alloc")]
+use serde::de::Error;
+
+use serde::de::{Deserialize, Deserializer};
+#[cfg(feature = "alloc")]
+use serde::ser::SerializeStruct;
+use serde::ser::{Serialize, Serializer};
+
+use crate::Precision;
+#[cfg(feature = "alloc")]
+use crate::{ApproxHash, ApproxHashMap, FloatPool};
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Precision")]
+struct PrecisionRepr {
+    absolute: i32,
+    relative: u32,
+}
+
+impl Serialize for Precision {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PrecisionRepr {
+            absolute: self.absolute_param(),
+            relative: self.relative_param(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Precision {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let PrecisionRepr { absolute, relative } = PrecisionRepr::deserialize(deserializer)?;
+        Ok(Precision::new(absolute, relative))
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "FloatPool")]
+struct FloatPoolRepr {
+    prec: Precision,
+    /// Pairs of buckets and the bit patterns of the floats in them.
+    buckets: Vec<(u64, u64)>,
+}
+
+#[cfg(feature = "alloc")]
+impl Serialize for FloatPool {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Store every bucket, not just the representatives, because which
+        // float a neighboring bucket belongs to depends on insertion order.
+        let buckets: Vec<(u64, u64)> = (self.buckets().into_iter())
+            .map(|(k, v)| (k, v.to_bits()))
+            .collect();
+
+        let mut state = serializer.serialize_struct("FloatPool", 2)?;
+        state.serialize_field("prec", &self.prec())?;
+        state.serialize_field("buckets", &buckets)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de> Deserialize<'de> for FloatPool {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let FloatPoolRepr { prec, buckets } = FloatPoolRepr::deserialize(deserializer)?;
+        let buckets = buckets.into_iter().map(|(k, v)| (k, f64::from_bits(v)));
+        FloatPool::from_buckets(prec, buckets).ok_or_else(|| {
+            D::Error::custom("float pool bucket contains a float from another bucket")
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Serializes the entries of a map as a sequence of key-value pairs.
+struct EntriesRepr<'a, K, V, S>(&'a ApproxHashMap<K, V, S>);
+
+#[cfg(feature = "alloc")]
+impl<K: Serialize, V: Serialize, S> Serialize for EntriesRepr<'_, K, V, S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "ApproxHashMap")]
+struct ApproxHashMapRepr<K, V> {
+    pool: FloatPool,
+    entries: Vec<(K, V)>,
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Serialize, V: Serialize, S> Serialize for ApproxHashMap<K, V, S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut state = serializer.serialize_struct("ApproxHashMap", 2)?;
+        state.serialize_field("pool", self.float_pool())?;
+        state.serialize_field("entries", &EntriesRepr(self))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de, K, V, S> Deserialize<'de> for ApproxHashMap<K, V, S>
+where
+    K: ApproxHash + Deserialize<'de>,
+    V: Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let ApproxHashMapRepr { pool, entries } = ApproxHashMapRepr::deserialize(deserializer)?;
+        let mut map = ApproxHashMap::with_hasher_and_float_pool(S::default(), pool);
+        map.extend(entries);
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precision_roundtrip() {
+        let prec = Precision::new(-5, 12);
+        let json = serde_json::to_string(&prec).unwrap();
+        assert_eq!(json, r#"{"absolute":-5,"relative":12}"#);
+        let prec2: Precision = serde_json::from_str(&json).unwrap();
+        assert_eq!(prec.absolute_param(), prec2.absolute_param());
+        assert_eq!(prec.relative_param(), prec2.relative_param());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_float_pool_roundtrip() {
+        let mut pool = FloatPool::new(Precision::absolute(3)); // bucket size = 0.125
+        for x in [0.1, 1.0 / 3.0, -2.7, 1e10] {
+            let _ = pool.intern(x);
+        }
+
+        let json = serde_json::to_string(&pool).unwrap();
+        let pool2: FloatPool = serde_json::from_str(&json).unwrap();
+        assert_eq!(pool.bucket_count(), pool2.bucket_count());
+        assert_eq!(format!("{pool:?}"), format!("{pool2:?}"));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_hash_map_roundtrip() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        map.insert([0.1, 1.0 / 3.0], 'a');
+        map.insert([0.6, -2.7], 'b');
+
+        let json = serde_json::to_string(&map).unwrap();
+        let map2: ApproxHashMap<[f64; 2], char> = serde_json::from_str(&json).unwrap();
+        assert_eq!(map2.len(), 2);
+        for (k, v) in &map {
+            let (k2, v2) = map2.get_key_value(k).unwrap();
+            assert_eq!(k.map(f64::to_bits), k2.map(f64::to_bits));
+            assert_eq!(v, v2);
+        }
+        assert_eq!(map2.get(&[-0.05, 0.3]), Some(&'a'));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_shared_neighbor_bucket_roundtrip() {
+        let mut map = ApproxHashMap::new(Precision::absolute(3)); // bucket size = 0.125
+        // Both of these occupy the bucket containing 1.2, but 1.26 was first.
+        map.insert(1.26, 'b');
+        map.insert(1.0, 'a');
+        let expected = map.get(&1.2);
+        assert_eq!(expected, Some(&'a'));
+
+        let json = serde_json::to_string(&map).unwrap();
+        let map2: ApproxHashMap<f64, char> = serde_json::from_str(&json).unwrap();
+        assert_eq!(map2.get(&1.2), expected);
+        assert_eq!(
+            format!("{:?}", map.float_pool()),
+            format!("{:?}", map2.float_pool())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_float_pool_invalid_bucket() {
+        let json = r#"{"prec":{"absolute":3,"relative":0},"buckets":[[0,4607182418800017408]]}"#;
+        assert!(serde_json::from_str::<FloatPool>(json).is_err());
+    }
+}
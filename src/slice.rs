@@ -0,0 +1,222 @@
+//! Extension traits for sorting, grouping, and searching slices of approximate
+//! values.
+
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use crate::hash_map::SORT_PREC;
+use crate::{ApproxEq, ApproxInternable, ApproxOrd, FloatPool, Precision};
+
+/// Extension trait for slices of approximate values.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{ApproxSliceExt, Precision};
+///
+/// let prec = Precision::absolute(3); // bucket size = 0.125
+/// let mut lengths = [3.0, 1.0, 2.0, 1.01, 0.99, 2.0];
+/// lengths.sort_approx(prec);
+/// assert_eq!(lengths, [1.0, 1.01, 0.99, 2.0, 2.0, 3.0]);
+///
+/// let groups: Vec<&[f64]> = lengths.group_by_approx(prec).collect();
+/// assert_eq!(groups, [&[1.0, 1.01, 0.99][..], &[2.0, 2.0], &[3.0]]);
+/// assert_eq!(lengths.binary_search_approx(prec, &2.99), Ok(5));
+/// assert_eq!(lengths.binary_search_approx(prec, &2.5), Err(5));
+/// ```
+pub trait ApproxSliceExt<T> {
+    /// Sorts the slice, where approximately equal elements are considered
+    /// equal.
+    ///
+    /// [`ApproxOrd::approx_cmp()`] is not transitive, so elements are first
+    /// interned using a temporary [`FloatPool`] and then compared. The sort is
+    /// stable, so elements that are interned to the same value keep their
+    /// original order. Elements themselves are not modified.
+    fn sort_approx(&mut self, prec: Precision)
+    where
+        T: ApproxOrd + ApproxInternable + Clone;
+
+    /// Sorts the slice by a key extracted from each element, where
+    /// approximately equal keys are considered equal.
+    ///
+    /// Keys are interned and compared as in
+    /// [`sort_approx()`](ApproxSliceExt::sort_approx). `f` is called exactly
+    /// once per element.
+    fn sort_by_approx_key<K, F>(&mut self, prec: Precision, f: F)
+    where
+        K: ApproxOrd + ApproxInternable,
+        F: FnMut(&T) -> K;
+
+    /// Returns an iterator over runs of consecutive elements that are
+    /// approximately equal to the first element of the run.
+    ///
+    /// If the slice is sorted by [`sort_approx()`](ApproxSliceExt::sort_approx)
+    /// using the same precision, then each run contains all the elements that
+    /// were interned to the same value.
+    fn group_by_approx(&self, prec: Precision) -> GroupByApprox<'_, T>
+    where
+        T: ApproxEq;
+
+    /// Binary searches a sorted slice for an element approximately equal to
+    /// `x`.
+    ///
+    /// This behaves like [`slice::binary_search()`], but compares elements
+    /// using [`ApproxOrd::approx_cmp()`].
+    fn binary_search_approx(&self, prec: Precision, x: &T) -> Result<usize, usize>
+    where
+        T: ApproxOrd;
+}
+
+impl<T> ApproxSliceExt<T> for [T] {
+    fn sort_approx(&mut self, prec: Precision)
+    where
+        T: ApproxOrd + ApproxInternable + Clone,
+    {
+        self.sort_by_approx_key(prec, T::clone);
+    }
+
+    fn sort_by_approx_key<K, F>(&mut self, prec: Precision, mut f: F)
+    where
+        K: ApproxOrd + ApproxInternable,
+        F: FnMut(&T) -> K,
+    {
+        let mut pool = FloatPool::new(prec);
+        let mut keys: Vec<(K, usize)> = self
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (pool.intern(f(x)), i))
+            .collect();
+        keys.sort_by(|(k1, _), (k2, _)| k1.approx_cmp(k2, SORT_PREC));
+        let mut order: Vec<usize> = keys.into_iter().map(|(_, i)| i).collect();
+
+        // Apply the permutation one cycle at a time, marking each position as
+        // done once it holds the correct element.
+        for start in 0..order.len() {
+            let mut i = start;
+            loop {
+                let j = order[i];
+                order[i] = i;
+                if j == start {
+                    break;
+                }
+                self.swap(i, j);
+                i = j;
+            }
+        }
+    }
+
+    fn group_by_approx(&self, prec: Precision) -> GroupByApprox<'_, T>
+    where
+        T: ApproxEq,
+    {
+        GroupByApprox { slice: self, prec }
+    }
+
+    fn binary_search_approx(&self, prec: Precision, x: &T) -> Result<usize, usize>
+    where
+        T: ApproxOrd,
+    {
+        self.binary_search_by(|y| y.approx_cmp(x, prec))
+    }
+}
+
+/// Extension trait for vectors of approximate values.
+pub trait ApproxVecExt<T> {
+    /// Removes consecutive elements that are approximately equal to the
+    /// element before them that was kept.
+    ///
+    /// If the vector is sorted by
+    /// [`sort_approx()`](ApproxSliceExt::sort_approx) using the same
+    /// precision, then this keeps the first element of each run returned by
+    /// [`group_by_approx()`](ApproxSliceExt::group_by_approx).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use approx_collections::{ApproxVecExt, Precision};
+    ///
+    /// let mut v = vec![1.0, 0.1 + 0.2, 0.3, 1.0, 1.0];
+    /// v.dedup_approx(Precision::DEFAULT);
+    /// assert_eq!(v, [1.0, 0.1 + 0.2, 1.0]);
+    /// ```
+    fn dedup_approx(&mut self, prec: Precision)
+    where
+        T: ApproxEq;
+}
+
+impl<T> ApproxVecExt<T> for Vec<T> {
+    fn dedup_approx(&mut self, prec: Precision)
+    where
+        T: ApproxEq,
+    {
+        self.dedup_by(|a, b| a.approx_eq(b, prec));
+    }
+}
+
+/// An iterator over runs of approximately equal elements in a slice.
+///
+/// This `struct` is created by the [`group_by_approx`] method on
+/// [`ApproxSliceExt`].
+///
+/// [`group_by_approx`]: ApproxSliceExt::group_by_approx
+#[derive(Debug, Clone)]
+pub struct GroupByApprox<'a, T> {
+    slice: &'a [T],
+    prec: Precision,
+}
+impl<T: ApproxEq> FusedIterator for GroupByApprox<'_, T> {}
+impl<'a, T: ApproxEq> Iterator for GroupByApprox<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.slice.first()?;
+        let len = (self.slice.iter())
+            .position(|x| !x.approx_eq(first, self.prec))
+            .unwrap_or(self.slice.len());
+        let (group, rest) = self.slice.split_at(len);
+        self.slice = rest;
+        Some(group)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::from(!self.slice.is_empty()), Some(self.slice.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_approx() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut v = [5.0, 1.0, 1.1, 0.95, 5.01, 1.3, -2.0, 1.06];
+        v.sort_approx(prec);
+        // 1.1, 0.95, and 1.06 are interned to 1.0, but 1.3 is not.
+        assert_eq!(v, [-2.0, 1.0, 1.1, 0.95, 1.06, 1.3, 5.0, 5.01]);
+
+        let mut points = [([2.0, 0.0], 'a'), ([1.0, 3.0], 'b'), ([1.01, 2.0], 'c')];
+        points.sort_by_approx_key(prec, |(p, _)| *p);
+        let names: Vec<char> = points.iter().map(|&(_, c)| c).collect();
+        assert_eq!(names, ['c', 'b', 'a']);
+
+        let mut empty: [f64; 0] = [];
+        empty.sort_approx(prec);
+    }
+
+    #[test]
+    fn test_group_and_search_approx() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let v = [1.0, 1.1, 1.3, 2.0, 2.01, 3.0];
+        let groups: Vec<&[f64]> = v.group_by_approx(prec).collect();
+        assert_eq!(groups, [&[1.0, 1.1][..], &[1.3], &[2.0, 2.01], &[3.0]]);
+        assert_eq!([0.0; 0].group_by_approx(prec).next(), None);
+
+        assert_eq!(v.binary_search_approx(prec, &2.99), Ok(5));
+        assert_eq!(v.binary_search_approx(prec, &2.5), Err(5));
+
+        let mut v = v.to_vec();
+        v.dedup_approx(prec);
+        assert_eq!(v, [1.0, 1.3, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,285 @@
+//! Compact binary snapshot format for [`FloatPool`].
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::{FloatPool, Precision};
+
+impl FloatPool {
+    /// Writes the pool to `writer` in a compact binary format that can be read
+    /// using [`FloatPool::read_snapshot()`].
+    ///
+    /// Only the precision and the bit patterns of the floats in the pool are
+    /// stored, followed by a checksum. Neighboring buckets are reconstructed
+    /// when the snapshot is read.
+    pub fn write_snapshot<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut floats: Vec<u64> = self.iter().map(f64::to_bits).collect();
+        floats.sort_unstable();
+
+        let mut w = ChecksumWriter::new(writer);
+        w.write_all(&SNAPSHOT_MAGIC)?;
+        w.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        w.write_all(&self.prec().absolute_param().to_le_bytes())?;
+        w.write_all(&self.prec().relative_param().to_le_bytes())?;
+        w.write_all(&(floats.len() as u64).to_le_bytes())?;
+        for bits in floats {
+            w.write_all(&bits.to_le_bytes())?;
+        }
+        let checksum = w.checksum;
+        w.inner.write_all(&checksum.to_le_bytes())?;
+        w.inner.flush()
+    }
+
+    /// Reads a pool from `reader` that was written using
+    /// [`FloatPool::write_snapshot()`].
+    ///
+    /// Returns an error if the snapshot is malformed, was written by an
+    /// incompatible version of this crate, or uses a precision that sorts
+    /// floats into different buckets than `prec`. If anything after the
+    /// version is corrupted or truncated, this returns
+    /// [`SnapshotError::ChecksumMismatch`] before comparing the precision or
+    /// interning any floats.
+    pub fn read_snapshot<R: Read>(reader: R, prec: Precision) -> Result<Self, SnapshotError> {
+        let mut r = ChecksumReader::new(reader);
+        if r.read_array()? != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::InvalidMagic);
+        }
+        let version = u32::from_le_bytes(r.read_array()?);
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        // Verify the checksum before checking the precision or interning any
+        // floats. The count may be corrupted, so running out of data is a
+        // checksum mismatch as well.
+        let (found, floats) = match read_snapshot_payload(&mut r) {
+            Ok(Some(payload)) => payload,
+            Ok(None) => return Err(SnapshotError::ChecksumMismatch),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(SnapshotError::ChecksumMismatch);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if !found.has_same_buckets(prec) {
+            return Err(SnapshotError::PrecisionMismatch {
+                expected: prec,
+                found,
+            });
+        }
+
+        let mut pool = FloatPool::new(prec);
+        for bits in floats {
+            pool.insert(f64::from_bits(bits));
+        }
+        Ok(pool)
+    }
+}
+
+/// Reads the precision and the bit patterns of the floats in a snapshot,
+/// followed by the checksum. Returns `None` if the checksum does not match.
+fn read_snapshot_payload<R: Read>(
+    r: &mut ChecksumReader<R>,
+) -> io::Result<Option<(Precision, Vec<u64>)>> {
+    let absolute = i32::from_le_bytes(r.read_array()?);
+    let relative = u32::from_le_bytes(r.read_array()?);
+    let prec = Precision::new(absolute, relative);
+
+    let count = u64::from_le_bytes(r.read_array()?);
+    let mut floats = vec![];
+    for _ in 0..count {
+        floats.push(u64::from_le_bytes(r.read_array()?));
+    }
+
+    let expected_checksum = r.checksum;
+    let mut checksum_bytes = [0; 8];
+    r.inner.read_exact(&mut checksum_bytes)?;
+    Ok((u64::from_le_bytes(checksum_bytes) == expected_checksum).then_some((prec, floats)))
+}
+
+/// Magic bytes at the start of a [`FloatPool`] snapshot.
+const SNAPSHOT_MAGIC: [u8; 4] = *b"AXFP";
+/// Current version of the [`FloatPool`] snapshot format.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Error returned by [`FloatPool::read_snapshot()`].
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Error reading from the underlying reader.
+    Io(io::Error),
+    /// The data does not start with the magic bytes for a snapshot.
+    InvalidMagic,
+    /// The snapshot was written using an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The snapshot uses a different precision than expected.
+    PrecisionMismatch {
+        /// Precision that was requested.
+        expected: Precision,
+        /// Precision stored in the snapshot.
+        found: Precision,
+    },
+    /// The checksum stored in the snapshot does not match its contents, or the
+    /// snapshot ends before its checksum.
+    ChecksumMismatch,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "error reading float pool snapshot: {e}"),
+            SnapshotError::InvalidMagic => write!(f, "data is not a float pool snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(f, "unsupported float pool snapshot version {v}")
+            }
+            SnapshotError::PrecisionMismatch { expected, found } => write!(
+                f,
+                "float pool snapshot has precision \
+                 (absolute={}, relative={}) but expected \
+                 (absolute={}, relative={})",
+                found.absolute_param(),
+                found.relative_param(),
+                expected.absolute_param(),
+                expected.relative_param(),
+            ),
+            SnapshotError::ChecksumMismatch => write!(f, "float pool snapshot checksum mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(value: io::Error) -> Self {
+        SnapshotError::Io(value)
+    }
+}
+
+/// 64-bit FNV-1a offset basis.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+/// 64-bit FNV-1a prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Writer that computes a checksum of everything written through it.
+struct ChecksumWriter<W> {
+    inner: W,
+    checksum: u64,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    fn new(inner: W) -> Self {
+        let checksum = FNV_OFFSET_BASIS;
+        Self { inner, checksum }
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.checksum = fnv1a(self.checksum, bytes);
+        self.inner.write_all(bytes)
+    }
+}
+
+/// Reader that computes a checksum of everything read through it.
+struct ChecksumReader<R> {
+    inner: R,
+    checksum: u64,
+}
+
+impl<R: Read> ChecksumReader<R> {
+    fn new(inner: R) -> Self {
+        let checksum = FNV_OFFSET_BASIS;
+        Self { inner, checksum }
+    }
+
+    fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0; N];
+        self.inner.read_exact(&mut bytes)?;
+        self.checksum = fnv1a(self.checksum, &bytes);
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let mut pool = FloatPool::new(prec);
+        for x in [0.1, 0.73, 2.1, -5.5, 1e100] {
+            let _ = pool.intern(x);
+        }
+
+        let mut bytes = vec![];
+        pool.write_snapshot(&mut bytes).unwrap();
+        // header + 5 floats (including 0.0, which 0.1 is interned to) + checksum
+        assert_eq!(bytes.len(), 24 + 5 * 8 + 8);
+
+        let pool2 = FloatPool::read_snapshot(bytes.as_slice(), prec).unwrap();
+        assert_eq!(format!("{pool:?}"), format!("{pool2:?}"));
+    }
+
+    #[test]
+    fn test_snapshot_errors() {
+        let prec = Precision::absolute(3);
+        let mut bytes = vec![];
+        FloatPool::new(prec).write_snapshot(&mut bytes).unwrap();
+
+        let result = FloatPool::read_snapshot(bytes.as_slice(), Precision::absolute(4));
+        assert!(matches!(
+            result,
+            Err(SnapshotError::PrecisionMismatch { .. })
+        ));
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 2;
+        let result = FloatPool::read_snapshot(wrong_version.as_slice(), prec);
+        assert!(matches!(result, Err(SnapshotError::UnsupportedVersion(2))));
+
+        let mut corrupted = bytes.clone();
+        corrupted[24] ^= 1;
+        let result = FloatPool::read_snapshot(corrupted.as_slice(), prec);
+        assert!(matches!(result, Err(SnapshotError::ChecksumMismatch)));
+
+        let mut corrupted_count = bytes.clone();
+        corrupted_count[16] ^= 1;
+        let result = FloatPool::read_snapshot(corrupted_count.as_slice(), prec);
+        assert!(matches!(result, Err(SnapshotError::ChecksumMismatch)));
+        corrupted_count[16] ^= 3;
+        let result = FloatPool::read_snapshot(corrupted_count.as_slice(), prec);
+        assert!(matches!(result, Err(SnapshotError::ChecksumMismatch)));
+
+        let truncated = &bytes[..bytes.len() - 1];
+        let result = FloatPool::read_snapshot(truncated, prec);
+        assert!(matches!(result, Err(SnapshotError::ChecksumMismatch)));
+
+        let mut corrupted_prec = bytes.clone();
+        corrupted_prec[8] ^= 1;
+        let result = FloatPool::read_snapshot(corrupted_prec.as_slice(), prec);
+        assert!(matches!(result, Err(SnapshotError::ChecksumMismatch)));
+
+        let truncated = &bytes[..10];
+        let result = FloatPool::read_snapshot(truncated, prec);
+        assert!(matches!(result, Err(SnapshotError::ChecksumMismatch)));
+
+        let truncated = &bytes[..6];
+        let result = FloatPool::read_snapshot(truncated, prec);
+        assert!(matches!(result, Err(SnapshotError::Io(_))));
+
+        let result = FloatPool::read_snapshot(&b"not a snapshot"[..], prec);
+        assert!(matches!(result, Err(SnapshotError::InvalidMagic)));
+    }
+}
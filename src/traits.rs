@@ -1,7 +1,10 @@
 //! Common traits related to approximate equality.
 
-use std::{
-    cmp::Ordering,
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use core::{
+    borrow::Borrow,
+    cmp::{Ordering, Reverse},
     hash::{Hash, Hasher},
 };
 
@@ -24,7 +27,7 @@ macro_rules! impl_for_tuples {
 
 /// Trait for types that can be approximately compared for equality with each
 /// other.
-pub trait ApproxEq: std::fmt::Debug {
+pub trait ApproxEq: core::fmt::Debug {
     /// Returns whether `self` and `other` are approximately equal according to
     /// the precision.
     fn approx_eq(&self, other: &Self, prec: Precision) -> bool;
@@ -41,7 +44,7 @@ impl ApproxEq for f32 {
 }
 impl<T: ApproxEq> ApproxEq for [T] {
     fn approx_eq(&self, other: &Self, prec: Precision) -> bool {
-        self.len() == other.len() && std::iter::zip(self, other).all(|(a, b)| a.approx_eq(b, prec))
+        self.len() == other.len() && core::iter::zip(self, other).all(|(a, b)| a.approx_eq(b, prec))
     }
 }
 impl<T: ApproxEq, const N: usize> ApproxEq for [T; N] {
@@ -49,16 +52,44 @@ impl<T: ApproxEq, const N: usize> ApproxEq for [T; N] {
         <[T]>::approx_eq(self, other, prec)
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxEq> ApproxEq for Vec<T> {
     fn approx_eq(&self, other: &Self, prec: Precision) -> bool {
         <[T]>::approx_eq(self, other, prec)
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxEq> ApproxEq for Box<T> {
     fn approx_eq(&self, other: &Self, prec: Precision) -> bool {
         T::approx_eq(self, other, prec)
     }
 }
+#[cfg(feature = "std")]
+impl<K, V, S> ApproxEq for std::collections::HashMap<K, V, S>
+where
+    K: Eq + Hash + core::fmt::Debug,
+    V: ApproxEq,
+    S: core::hash::BuildHasher,
+{
+    /// Returns whether both maps have exactly the same keys and the values
+    /// for each key are approximately equal.
+    fn approx_eq(&self, other: &Self, prec: Precision) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(k, v)| other.get(k).is_some_and(|v2| v.approx_eq(v2, prec)))
+    }
+}
+#[cfg(feature = "alloc")]
+impl<K: Ord + core::fmt::Debug, V: ApproxEq> ApproxEq for BTreeMap<K, V> {
+    /// Returns whether both maps have exactly the same keys and the values
+    /// for each key are approximately equal.
+    fn approx_eq(&self, other: &Self, prec: Precision) -> bool {
+        self.len() == other.len()
+            && core::iter::zip(self, other)
+                .all(|((k1, v1), (k2, v2))| k1 == k2 && v1.approx_eq(v2, prec))
+    }
+}
 impl<T: ApproxEq + ?Sized> ApproxEq for &T {
     fn approx_eq(&self, other: &Self, prec: Precision) -> bool {
         T::approx_eq(self, other, prec)
@@ -73,6 +104,11 @@ impl<T: ApproxEq> ApproxEq for Option<T> {
         }
     }
 }
+impl<T: ApproxEq> ApproxEq for Reverse<T> {
+    fn approx_eq(&self, other: &Self, prec: Precision) -> bool {
+        self.0.approx_eq(&other.0, prec)
+    }
+}
 macro_rules! impl_approx_eq_for_tuple {
     ($($generic_param:ident),+; $($index:tt),+) => {
         impl<$($generic_param: ApproxEq,)+> ApproxEq for ($($generic_param,)+) {
@@ -112,11 +148,13 @@ impl<T: ApproxEqZero, const N: usize> ApproxEqZero for [T; N] {
         <[T]>::approx_eq_zero(self, prec)
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxEqZero> ApproxEqZero for Vec<T> {
     fn approx_eq_zero(&self, prec: Precision) -> bool {
         <[T]>::approx_eq_zero(self, prec)
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxEqZero> ApproxEqZero for Box<T> {
     fn approx_eq_zero(&self, prec: Precision) -> bool {
         T::approx_eq_zero(self, prec)
@@ -164,7 +202,7 @@ impl ApproxOrd for f32 {
 }
 impl<T: ApproxOrd> ApproxOrd for [T] {
     fn approx_cmp(&self, other: &Self, prec: Precision) -> Ordering {
-        std::iter::zip(self, other)
+        core::iter::zip(self, other)
             .map(|(a, b)| a.approx_cmp(b, prec))
             .find(|&ord| ord != Ordering::Equal)
             .unwrap_or_else(|| self.len().cmp(&other.len()))
@@ -175,11 +213,13 @@ impl<T: ApproxOrd, const N: usize> ApproxOrd for [T; N] {
         <[T]>::approx_cmp(self, other, prec)
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxOrd> ApproxOrd for Vec<T> {
     fn approx_cmp(&self, other: &Self, prec: Precision) -> Ordering {
         <[T]>::approx_cmp(self, other, prec)
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxOrd> ApproxOrd for Box<T> {
     fn approx_cmp(&self, other: &Self, prec: Precision) -> Ordering {
         T::approx_cmp(self, other, prec)
@@ -200,6 +240,11 @@ impl<T: ApproxOrd> ApproxOrd for Option<T> {
         }
     }
 }
+impl<T: ApproxOrd> ApproxOrd for Reverse<T> {
+    fn approx_cmp(&self, other: &Self, prec: Precision) -> Ordering {
+        other.0.approx_cmp(&self.0, prec)
+    }
+}
 macro_rules! impl_approx_ord_for_tuple {
     ($($generic_param:ident),+; $($index:tt),+) => {
         impl<$($generic_param: ApproxOrd,)+> ApproxOrd for ($($generic_param,)+) {
@@ -236,6 +281,7 @@ impl ApproxCmpZero for f64 {
         }
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxCmpZero> ApproxCmpZero for Box<T> {
     fn approx_cmp_zero(&self, prec: Precision) -> Ordering {
         T::approx_cmp_zero(self, prec)
@@ -285,7 +331,7 @@ impl ApproxInternable for f32 {
 
 impl<T: ApproxInternable> ApproxInternable for [T] {
     fn intern_floats<F: FnMut(&mut f64)>(&mut self, f: &mut F) {
-        self.into_iter().for_each(|x| x.intern_floats(f));
+        self.iter_mut().for_each(|x| x.intern_floats(f));
     }
 }
 
@@ -295,11 +341,13 @@ impl<T: ApproxInternable, const N: usize> ApproxInternable for [T; N] {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<T: ApproxInternable> ApproxInternable for Vec<T> {
     fn intern_floats<F: FnMut(&mut f64)>(&mut self, f: &mut F) {
         <[T]>::intern_floats(self, f);
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxInternable> ApproxInternable for Box<T> {
     fn intern_floats<F: FnMut(&mut f64)>(&mut self, f: &mut F) {
         T::intern_floats(self, f);
@@ -319,6 +367,12 @@ impl<T: ApproxInternable> ApproxInternable for Option<T> {
         }
     }
 }
+
+impl<T: ApproxInternable> ApproxInternable for Reverse<T> {
+    fn intern_floats<F: FnMut(&mut f64)>(&mut self, f: &mut F) {
+        self.0.intern_floats(f);
+    }
+}
 /// Trait for types that can be stored in a [`crate::ApproxHashMap`].
 pub trait ApproxHash: ApproxInternable {
     /// Returns whether `self` and `other` are exactly equal, assuming both have
@@ -349,7 +403,7 @@ impl ApproxHash for f32 {
 }
 impl<T: ApproxHash> ApproxHash for [T] {
     fn interned_eq(&self, other: &Self) -> bool {
-        self.len() == other.len() && std::iter::zip(self, other).all(|(a, b)| a.interned_eq(b))
+        self.len() == other.len() && core::iter::zip(self, other).all(|(a, b)| a.interned_eq(b))
     }
 
     fn interned_hash<H: Hasher>(&self, state: &mut H) {
@@ -366,6 +420,7 @@ impl<T: ApproxHash, const N: usize> ApproxHash for [T; N] {
         <[T]>::interned_hash(self, state);
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxHash> ApproxHash for Vec<T> {
     fn interned_eq(&self, other: &Self) -> bool {
         <[T]>::interned_eq(self, other)
@@ -375,6 +430,7 @@ impl<T: ApproxHash> ApproxHash for Vec<T> {
         <[T]>::interned_hash(self, state);
     }
 }
+#[cfg(feature = "alloc")]
 impl<T: ApproxHash> ApproxHash for Box<T> {
     fn interned_eq(&self, other: &Self) -> bool {
         T::interned_eq(self, other)
@@ -403,12 +459,21 @@ impl<T: ApproxHash> ApproxHash for Option<T> {
     }
 
     fn interned_hash<H: Hasher>(&self, state: &mut H) {
-        std::mem::discriminant(self).hash(state);
+        core::mem::discriminant(self).hash(state);
         if let Some(inner) = self {
             inner.interned_hash(state);
         }
     }
 }
+impl<T: ApproxHash> ApproxHash for Reverse<T> {
+    fn interned_eq(&self, other: &Self) -> bool {
+        self.0.interned_eq(&other.0)
+    }
+
+    fn interned_hash<H: Hasher>(&self, state: &mut H) {
+        self.0.interned_hash(state);
+    }
+}
 macro_rules! impl_approx_internable_for_tuple {
     ($($generic_param:ident),+; $($index:tt),+) => {
         impl<$($generic_param: ApproxInternable,)+> ApproxInternable for ($($generic_param,)+) {
@@ -434,3 +499,302 @@ macro_rules! impl_approx_hash_for_tuple {
 }
 impl_for_tuples!(impl_approx_internable_for_tuple);
 impl_for_tuples!(impl_approx_hash_for_tuple);
+
+/// Trait for types that can be used to look up keys in a
+/// [`crate::ApproxHashMap`] without being interned in place, such as borrowed
+/// forms of keys.
+///
+/// Each method takes a function `f` that returns the interned float
+/// corresponding to a float, or `None` if there is none.
+///
+/// Nothing checks that an implementation hashes the same way as
+/// [`ApproxHash`]; if it does not, lookups may fail to find keys that are
+/// present. Use `#[derive(ApproxLookup)]` to keep them consistent, or look up
+/// keys by value using [`crate::ApproxHashMap::get_owned()`] and similar
+/// methods, which do not require this trait.
+pub trait ApproxLookup {
+    /// Hashes the object as if every float `x` had been replaced by `f(x)`,
+    /// exactly as [`ApproxHash::interned_hash()`] would. Returns `None` if `f`
+    /// returns `None` for any float.
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher;
+
+    /// Returns whether `interned` is exactly equal to the object with every
+    /// float `x` replaced by `f(x)`, exactly as [`ApproxHash::interned_eq()`]
+    /// would.
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>;
+}
+impl ApproxLookup for f64 {
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        f(*self)?.interned_hash(state);
+        Some(())
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        f(*self).is_some_and(|x| x.interned_eq(interned))
+    }
+}
+impl ApproxLookup for f32 {
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        (f(*self as f64)? as f32).interned_hash(state);
+        Some(())
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        f(*self as f64).is_some_and(|x| (x as f32).interned_eq(interned))
+    }
+}
+impl<T: ApproxLookup> ApproxLookup for [T] {
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        self.len().hash(state);
+        self.iter().try_for_each(|x| x.lookup_hash(f, state))
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        self.len() == interned.len()
+            && core::iter::zip(self, interned).all(|(a, b)| a.lookup_eq(b, f))
+    }
+}
+impl<T: ApproxLookup, const N: usize> ApproxLookup for [T; N] {
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        <[T]>::lookup_hash(self, f, state)
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        <[T]>::lookup_eq(self, interned, f)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: ApproxLookup> ApproxLookup for Vec<T> {
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        <[T]>::lookup_hash(self, f, state)
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        <[T]>::lookup_eq(self, interned, f)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: ApproxLookup> ApproxLookup for Box<T> {
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        T::lookup_hash(self, f, state)
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        T::lookup_eq(self, interned, f)
+    }
+}
+impl<T: ApproxLookup> ApproxLookup for &mut T {
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        T::lookup_hash(self, f, state)
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        T::lookup_eq(self, interned, f)
+    }
+}
+impl<T: ApproxLookup> ApproxLookup for Option<T> {
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            Some(inner) => inner.lookup_hash(f, state),
+            None => Some(()),
+        }
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        match (self, interned) {
+            (None, None) => true,
+            (None, Some(_)) | (Some(_), None) => false,
+            (Some(a), Some(b)) => a.lookup_eq(b, f),
+        }
+    }
+}
+impl<T: ApproxLookup> ApproxLookup for Reverse<T> {
+    fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        self.0.lookup_hash(f, state)
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        self.0.lookup_eq(&interned.0, f)
+    }
+}
+macro_rules! impl_approx_lookup_for_tuple {
+    ($($generic_param:ident),+; $($index:tt),+) => {
+        impl<$($generic_param: ApproxLookup,)+> ApproxLookup for ($($generic_param,)+) {
+            fn lookup_hash<F, H>(&self, f: &mut F, state: &mut H) -> Option<()>
+            where
+                F: FnMut(f64) -> Option<f64>,
+                H: Hasher,
+            {
+                $(self.$index.lookup_hash(f, state)?;)+
+                Some(())
+            }
+
+            fn lookup_eq<F>(&self, interned: &Self, f: &mut F) -> bool
+            where
+                F: FnMut(f64) -> Option<f64>,
+            {
+                $(self.$index.lookup_eq(&interned.$index, f))&&+
+            }
+        }
+    };
+}
+impl_for_tuples!(impl_approx_lookup_for_tuple);
+
+/// Trait for [`ApproxHash`] keys that can be looked up using a borrowed form
+/// `Q`.
+///
+/// Implementing this trait asserts that [`ApproxLookup`] on `Q` hashes and
+/// compares a borrowed key exactly as [`ApproxHash`] does on the owned key
+/// once it is interned. Otherwise lookups may fail to find keys that are
+/// present.
+pub trait ApproxBorrow<Q: ?Sized + ApproxLookup>: ApproxHash + Borrow<Q> {}
+impl<K: ApproxHash + ApproxLookup> ApproxBorrow<K> for K {}
+impl<T: ApproxHash + ApproxLookup, const N: usize> ApproxBorrow<[T]> for [T; N] {}
+#[cfg(feature = "alloc")]
+impl<T: ApproxHash + ApproxLookup> ApproxBorrow<[T]> for Vec<T> {}
+#[cfg(feature = "alloc")]
+impl<T: ApproxHash + ApproxLookup> ApproxBorrow<T> for Box<T> {}
+
+/// Wrapper for a value without floats that is compared exactly using [`Eq`],
+/// [`Ord`], and [`Hash`], so that it can be used with approximate values.
+///
+/// For example, `ApproxHashMap<(Exact<u32>, f64), V>` has keys that consist of
+/// an exact ID and an approximate float.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Exact<T>(pub T);
+impl<T: core::fmt::Debug + Eq> ApproxEq for Exact<T> {
+    fn approx_eq(&self, other: &Self, _prec: Precision) -> bool {
+        self == other
+    }
+}
+impl<T: core::fmt::Debug + Ord> ApproxOrd for Exact<T> {
+    fn approx_cmp(&self, other: &Self, _prec: Precision) -> Ordering {
+        self.cmp(other)
+    }
+}
+impl<T> ApproxInternable for Exact<T> {
+    fn intern_floats<F: FnMut(&mut f64)>(&mut self, _f: &mut F) {}
+}
+impl<T: Hash + Eq> ApproxHash for Exact<T> {
+    fn interned_eq(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn interned_hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+impl<T: Hash + Eq> ApproxLookup for Exact<T> {
+    fn lookup_hash<F, H>(&self, _f: &mut F, state: &mut H) -> Option<()>
+    where
+        F: FnMut(f64) -> Option<f64>,
+        H: Hasher,
+    {
+        self.0.hash(state);
+        Some(())
+    }
+
+    fn lookup_eq<F>(&self, interned: &Self, _f: &mut F) -> bool
+    where
+        F: FnMut(f64) -> Option<f64>,
+    {
+        self == interned
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_std_map_approx_eq() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let a = std::collections::HashMap::from([("x", 1.0), ("y", 2.0)]);
+        let b = std::collections::HashMap::from([("x", 1.01), ("y", 1.99)]);
+        let c = std::collections::HashMap::from([("x", 1.01), ("z", 1.99)]);
+        assert!(a.approx_eq(&b, prec));
+        assert!(!a.approx_eq(&c, prec));
+        assert!(!a.approx_eq(&b, Precision::absolute(10)));
+    }
+
+    #[test]
+    fn test_btree_map_approx_eq() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let a = BTreeMap::from([(1, [0.5, 1.0]), (2, [3.0, 4.0])]);
+        let b = BTreeMap::from([(1, [0.49, 1.01]), (2, [3.0, 4.0])]);
+        let c = BTreeMap::from([(1, [0.49, 1.01])]);
+        assert!(a.approx_eq(&b, prec));
+        assert!(!a.approx_eq(&c, prec));
+        assert!(!a.approx_eq(&b, Precision::absolute(10)));
+    }
+}
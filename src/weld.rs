@@ -0,0 +1,238 @@
+//! Vertex welding, which turns a list of vertices into a list of unique
+//! vertices and an index buffer.
+
+use alloc::vec::Vec;
+
+use crate::hash_map::{ApproxHashMap, Entry};
+use crate::{ApproxHash, FloatPool, Precision};
+
+/// Merges approximately equal vertices, such as for turning triangle soup into
+/// an indexed mesh.
+///
+/// This is shorthand for `Welder::new(prec).weld(vertices)`. See [`Welder`]
+/// for using a different precision for each attribute of the vertices.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{Precision, weld};
+///
+/// let triangles = [
+///     [0.0, 0.0], [1.0, 0.0], [0.0, 1.0],
+///     [0.0, 1.0], [1.0, 0.1 + 0.2 - 0.3], [1.0, 1.0],
+/// ];
+/// let welded = weld(Precision::DEFAULT, triangles);
+///
+/// assert_eq!(welded.vertices, [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]);
+/// assert_eq!(welded.indices, [0, 1, 2, 2, 1, 3]);
+/// assert_eq!(welded.stats.merged_count(), 2);
+/// ```
+pub fn weld<K, I>(prec: Precision, vertices: I) -> Welded<K>
+where
+    K: ApproxHash,
+    I: IntoIterator<Item = K>,
+{
+    Welder::new(prec).weld(vertices)
+}
+
+/// Configuration for merging approximately equal vertices, with an optional
+/// precision for each attribute.
+///
+/// Attributes are consecutive runs of floats, in the order they are visited by
+/// [`ApproxInternable::intern_floats()`](crate::ApproxInternable::intern_floats).
+/// Floats that are not part of any attribute use the default precision.
+///
+/// # Examples
+///
+/// ```
+/// use approx_collections::{Precision, Welder};
+///
+/// // Position, normal, and UV
+/// type Vertex = ([f64; 3], [f64; 3], [f64; 2]);
+/// let vertices: [Vertex; 3] = [
+///     ([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0]),
+///     ([0.0, 0.0, 0.0], [0.0, 0.001, 1.0], [0.0, 0.0]),
+///     ([0.0, 0.0, 0.001], [0.0, 0.0, 1.0], [0.0, 0.0]),
+/// ];
+///
+/// let welded = Welder::new(Precision::DEFAULT)
+///     .with_attribute(3, Precision::DEFAULT)
+///     .with_attribute(3, Precision::absolute(5))
+///     .weld(vertices);
+///
+/// assert_eq!(welded.indices, [0, 0, 1]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Welder {
+    default_prec: Precision,
+    /// Number of floats and precision for each attribute.
+    attributes: Vec<(usize, Precision)>,
+}
+
+impl Welder {
+    /// Constructs a welder that uses `prec` for all floats.
+    pub fn new(prec: Precision) -> Self {
+        Self {
+            default_prec: prec,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Adds an attribute consisting of the next `float_count` floats in each
+    /// vertex, which are compared using `prec`.
+    #[must_use = "with_attribute() returns a modified copy"]
+    pub fn with_attribute(mut self, float_count: usize, prec: Precision) -> Self {
+        self.attributes.push((float_count, prec));
+        self
+    }
+
+    /// Merges approximately equal vertices, and returns the unique vertices
+    /// along with an index into them for each of the original vertices.
+    ///
+    /// Unique vertices are returned in the order they first appeared, and
+    /// floats are interned so that the first one in each bucket is kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than `u32::MAX` unique vertices.
+    pub fn weld<K, I>(&self, vertices: I) -> Welded<K>
+    where
+        K: ApproxHash,
+        I: IntoIterator<Item = K>,
+    {
+        let mut pools: Vec<FloatPool> = (self.attributes.iter().map(|&(_, prec)| prec))
+            .chain([self.default_prec])
+            .map(FloatPool::new)
+            .collect();
+        // Floats are interned manually, so the map's own pool is not used.
+        let mut map: ApproxHashMap<K, u32> = ApproxHashMap::new(self.default_prec);
+        let mut indices = Vec::new();
+
+        for mut vertex in vertices {
+            let mut float_index = 0;
+            vertex.intern_floats(&mut |x| {
+                let pool = &mut pools[self.attribute_of(float_index)];
+                *x = pool.intern(*x);
+                float_index += 1;
+            });
+
+            let new_index = map.len();
+            let hash = map.hash_interned(&vertex);
+            let index = match map.entry_interned(hash, vertex) {
+                Entry::Occupied(e) => *e.get(),
+                Entry::Vacant(e) => {
+                    *e.insert(u32::try_from(new_index).expect("too many unique vertices"))
+                }
+            };
+            indices.push(index);
+        }
+
+        let mut unique: Vec<Option<K>> = core::iter::repeat_with(|| None).take(map.len()).collect();
+        for (vertex, i) in map {
+            unique[i as usize] = Some(vertex);
+        }
+        let vertices: Vec<K> = unique.into_iter().flatten().collect();
+
+        Welded {
+            stats: WeldStats {
+                input_count: indices.len(),
+                output_count: vertices.len(),
+            },
+            vertices,
+            indices,
+        }
+    }
+
+    /// Returns the index of the attribute containing the float at
+    /// `float_index`, or `self.attributes.len()` if it is not part of any
+    /// attribute.
+    fn attribute_of(&self, mut float_index: usize) -> usize {
+        for (i, &(float_count, _)) in self.attributes.iter().enumerate() {
+            match float_index.checked_sub(float_count) {
+                Some(rest) => float_index = rest,
+                None => return i,
+            }
+        }
+        self.attributes.len()
+    }
+}
+
+/// Result of merging approximately equal vertices.
+#[derive(Debug, Clone)]
+pub struct Welded<K> {
+    /// Unique vertices, in the order they first appeared.
+    pub vertices: Vec<K>,
+    /// Index into `vertices` for each of the original vertices.
+    pub indices: Vec<u32>,
+    /// Statistics about how many vertices were merged.
+    pub stats: WeldStats,
+}
+
+/// Statistics about how many vertices were merged by [`Welder::weld()`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct WeldStats {
+    /// Number of original vertices.
+    pub input_count: usize,
+    /// Number of unique vertices.
+    pub output_count: usize,
+}
+
+impl WeldStats {
+    /// Returns the number of vertices that were merged into an earlier vertex.
+    pub fn merged_count(self) -> usize {
+        self.input_count - self.output_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weld() {
+        let prec = Precision::absolute(3); // bucket size = 0.125
+        let welded = weld(
+            prec,
+            [
+                [1.0, 2.0],
+                [3.0, 4.0],
+                [1.01, 1.99],
+                [1.0, 3.0],
+                [2.99, 4.0],
+            ],
+        );
+        assert_eq!(welded.vertices, [[1.0, 2.0], [3.0, 4.0], [1.0, 3.0]]);
+        assert_eq!(welded.indices, [0, 1, 0, 2, 1]);
+        assert_eq!(
+            welded.stats,
+            WeldStats {
+                input_count: 5,
+                output_count: 3,
+            },
+        );
+        assert_eq!(welded.stats.merged_count(), 2);
+
+        let welded = weld::<f64, _>(prec, []);
+        assert!(welded.vertices.is_empty() && welded.indices.is_empty());
+    }
+
+    #[test]
+    fn test_weld_attributes() {
+        let welder = Welder::new(Precision::absolute(3)) // bucket size = 0.125
+            .with_attribute(1, Precision::absolute(10))
+            .with_attribute(2, Precision::absolute(1)); // bucket size = 0.5
+        assert_eq!(welder.attribute_of(0), 0);
+        assert_eq!(welder.attribute_of(2), 1);
+        assert_eq!(welder.attribute_of(3), 2);
+
+        let vertices = [
+            (1.0, [0.0, 0.0], 5.0),
+            (1.01, [0.0, 0.0], 5.0),
+            (1.0, [0.3, 0.0], 5.0),
+            (1.0, [0.0, 0.0], 5.01),
+        ];
+        let welded = welder.weld(vertices);
+        assert_eq!(welded.indices, [0, 1, 0, 0]);
+        assert_eq!(welded.vertices.len(), 2);
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg(all(feature = "derive", feature = "alloc"))]
 #![allow(dead_code)]
 #![allow(unused)]
 
@@ -5,7 +6,10 @@
 // included to test the proc macro to make sure the code it generates doesn't
 // throw any errors. Thus I allow dead code.
 
-use approx_collections::{ApproxEq, ApproxEqZero, ApproxInternable, FloatPool, Precision};
+use approx_collections::{
+    ApproxEq, ApproxEqZero, ApproxHash, ApproxHashMap, ApproxInternable, ApproxLookup, ApproxOrd,
+    FloatPool, Precision,
+};
 
 fn main() {}
 
@@ -217,3 +221,110 @@ fn test_intern() {
     let _ = pool.intern(five);
     assert_eq!(pool.bucket_count(), 13)
 }
+
+#[derive(Debug, Clone, ApproxInternable, ApproxHash, ApproxLookup)]
+struct HashKey {
+    pos: [f64; 2],
+    #[approx_internable_non_float]
+    id: u32,
+}
+
+// Maps need `ApproxOrd` keys to be formatted, which `ApproxEq` requires.
+impl ApproxEq for HashKey {
+    fn approx_eq(&self, other: &Self, prec: Precision) -> bool {
+        self.pos.approx_eq(&other.pos, prec) && self.id == other.id
+    }
+}
+impl ApproxOrd for HashKey {
+    fn approx_cmp(&self, other: &Self, prec: Precision) -> std::cmp::Ordering {
+        (self.pos.approx_cmp(&other.pos, prec)).then(self.id.cmp(&other.id))
+    }
+}
+
+#[derive(Debug, Clone, ApproxInternable, ApproxHash, ApproxLookup)]
+struct UnnamedHashKey(f32, #[approx_internable_non_float] u32);
+
+#[derive(Debug, Clone, ApproxInternable, ApproxHash, ApproxLookup)]
+struct UnitHashKey;
+
+#[derive(Debug, Clone, ApproxInternable, ApproxHash, ApproxLookup)]
+enum HashKeyEnum {
+    Empty,
+    Point(f64, f64),
+    Labeled {
+        x: f64,
+        #[approx_internable_non_float]
+        label: char,
+    },
+}
+
+#[derive(ApproxInternable, ApproxHash, ApproxLookup)]
+struct WrapperHash<'a, T: ApproxHash + ApproxLookup> {
+    data: &'a mut T,
+    other: Option<T>,
+}
+
+#[test]
+fn test_hash_lookup() {
+    let prec = Precision::absolute(3); // bucket size = 0.125
+    let mut map = ApproxHashMap::new(prec);
+    map.insert(
+        HashKey {
+            pos: [1.0, 2.0],
+            id: 0,
+        },
+        'a',
+    );
+    map.insert(
+        HashKey {
+            pos: [1.0, 2.0],
+            id: 1,
+        },
+        'b',
+    );
+    let key = |x, id| HashKey { pos: [x, 2.0], id };
+    assert_eq!(map.get(&key(1.01, 0)), Some(&'a'));
+    assert_eq!(map.get(&key(0.99, 1)), Some(&'b'));
+    assert!(!map.contains_key(&key(1.01, 2)));
+    assert!(!map.contains_key(&key(5.0, 0)));
+    assert_eq!(map.get_key_value(&key(1.01, 0)).unwrap().0.pos, [1.0, 2.0]);
+
+    let other = ApproxHashMap::from_iter(prec, [(key(0.99, 1), 'b'), (key(1.01, 0), 'a')]);
+    assert_eq!(map, other);
+    let values = ApproxHashMap::from_iter(prec, [(key(1.0, 0), 1.0)]);
+    let other = ApproxHashMap::from_iter(prec, [(key(0.99, 0), 1.01)]);
+    assert!(values.approx_eq(&other, prec));
+
+    let mut map = ApproxHashMap::new(prec);
+    map.insert(UnnamedHashKey(1.0, 3), 'a');
+    assert_eq!(map.get(&UnnamedHashKey(1.01, 3)), Some(&'a'));
+    assert_eq!(map.get(&UnnamedHashKey(1.01, 4)), None);
+
+    let mut map = ApproxHashMap::new(prec);
+    map.insert(UnitHashKey, 'a');
+    map.insert(UnitHashKey, 'b');
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.get(&UnitHashKey), Some(&'b'));
+
+    let mut map = ApproxHashMap::new(prec);
+    map.insert(HashKeyEnum::Empty, 'a');
+    map.insert(HashKeyEnum::Point(1.0, 2.0), 'b');
+    map.insert(HashKeyEnum::Labeled { x: 1.0, label: 'x' }, 'c');
+    assert_eq!(map.get(&HashKeyEnum::Empty), Some(&'a'));
+    assert_eq!(map.get(&HashKeyEnum::Point(1.01, 1.99)), Some(&'b'));
+    assert_eq!(
+        map.get(&HashKeyEnum::Labeled {
+            x: 1.01,
+            label: 'x'
+        }),
+        Some(&'c')
+    );
+    assert_eq!(
+        map.get(&HashKeyEnum::Labeled {
+            x: 1.01,
+            label: 'y'
+        }),
+        None
+    );
+    assert_eq!(map.get(&HashKeyEnum::Point(1.01, 3.0)), None);
+}